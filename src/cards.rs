@@ -0,0 +1,201 @@
+/// カードの枚数
+pub const NUM_CARDS: usize = 52;
+
+/// 2枚の手札の組み合わせの個数
+pub const NUM_COMBOS: usize = NUM_CARDS * (NUM_CARDS - 1) / 2;
+
+/// ランクを表す文字 (弱い順)
+pub const RANK_CHARS: [char; 13] = [
+    '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+];
+
+/// スートを表す文字
+pub const SUIT_CHARS: [char; 4] = ['c', 'd', 'h', 's'];
+
+/// カード `card` のランク (0: "2", ..., 12: "A") を返す
+#[inline]
+pub fn rank_of(card: usize) -> usize {
+    card / 4
+}
+
+/// カード `card` のスート (0: "c", 1: "d", 2: "h", 3: "s") を返す
+#[inline]
+pub fn suit_of(card: usize) -> usize {
+    card % 4
+}
+
+/// カード `card` を "As" のような文字列に変換する
+pub fn card_to_string(card: usize) -> String {
+    format!("{}{}", RANK_CHARS[rank_of(card)], SUIT_CHARS[suit_of(card)])
+}
+
+/// ランクを表す文字をランクに変換する
+pub fn parse_rank(c: char) -> Option<usize> {
+    RANK_CHARS.iter().position(|&r| r == c.to_ascii_uppercase())
+}
+
+/// スートを表す文字をスートに変換する
+pub fn parse_suit(c: char) -> Option<usize> {
+    SUIT_CHARS.iter().position(|&s| s == c.to_ascii_lowercase())
+}
+
+/// "As" のような文字列をカードに変換する
+pub fn parse_card(s: &str) -> Option<usize> {
+    let mut chars = s.chars();
+    let rank = parse_rank(chars.next()?)?;
+    let suit = parse_suit(chars.next()?)?;
+    match chars.next() {
+        None => Some(4 * rank + suit),
+        Some(_) => None,
+    }
+}
+
+/// カードの組 `(i, j)` (`i < j`) の手札の番号を返す
+///
+/// `PushFoldGame::evaluate()` などで用いられる三角形状の番号付けに従う
+#[inline]
+pub fn combo_index(i: usize, j: usize) -> usize {
+    debug_assert!(i < j && j < NUM_CARDS);
+    i * (2 * NUM_CARDS - i - 1) / 2 + (j - i - 1)
+}
+
+/// 手札の番号 `index` に対応するカードの組 `(i, j)` (`i < j`) を返す
+pub fn combo_cards(index: usize) -> (usize, usize) {
+    debug_assert!(index < NUM_COMBOS);
+    let mut k = index;
+    for i in 0..(NUM_CARDS - 1) {
+        let row_len = NUM_CARDS - 1 - i;
+        if k < row_len {
+            return (i, i + 1 + k);
+        }
+        k -= row_len;
+    }
+    unreachable!()
+}
+
+/// 2つの手札が同じカードを含まないかどうかを返す
+#[inline]
+pub fn combos_disjoint(a: usize, b: usize) -> bool {
+    let (i, j) = combo_cards(a);
+    let (m, n) = combo_cards(b);
+    i != m && i != n && j != m && j != n
+}
+
+/// 4つのスートの並べ替え (24通り) を返す
+pub fn suit_permutations() -> Vec<[usize; 4]> {
+    let mut ret = Vec::with_capacity(24);
+    for a in 0..4 {
+        for b in (0..4).filter(|&b| b != a) {
+            for c in (0..4).filter(|&c| c != a && c != b) {
+                ret.push([a, b, c, 6 - a - b - c]);
+            }
+        }
+    }
+    ret
+}
+
+/// 手札 `combo` のスートを `perm` に従って入れ替えた手札を返す
+pub fn permute_combo(combo: usize, perm: &[usize; 4]) -> usize {
+    let (i, j) = combo_cards(combo);
+    let i = 4 * rank_of(i) + perm[suit_of(i)];
+    let j = 4 * rank_of(j) + perm[suit_of(j)];
+    combo_index(i.min(j), i.max(j))
+}
+
+/// 手札の番号 `index` を "AsKh" のような文字列に変換する (強いカードが先)
+pub fn combo_to_string(index: usize) -> String {
+    let (i, j) = combo_cards(index);
+    format!("{}{}", card_to_string(j), card_to_string(i))
+}
+
+/// 手札の番号 `index` が属するハンドクラス `(高いランク, 低いランク, スーテッドか)` を返す
+#[inline]
+pub fn hand_class(index: usize) -> (usize, usize, bool) {
+    let (i, j) = combo_cards(index);
+    (rank_of(j), rank_of(i), suit_of(i) == suit_of(j))
+}
+
+/// ハンドクラスを "AKs", "AKo", "AA" のような文字列に変換する
+pub fn hand_class_to_string(high: usize, low: usize, suited: bool) -> String {
+    if high == low {
+        format!("{}{}", RANK_CHARS[high], RANK_CHARS[low])
+    } else {
+        let suffix = if suited { 's' } else { 'o' };
+        format!("{}{}{}", RANK_CHARS[high], RANK_CHARS[low], suffix)
+    }
+}
+
+/// デッキの種類
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Deck {
+    /// 52枚の通常のデッキ
+    #[default]
+    Standard,
+
+    /// 2から5を除いた36枚のショートデッキ (6+ ホールデム)
+    ShortDeck,
+}
+
+impl Deck {
+    /// デッキに含まれる最も低いランクを返す
+    #[inline]
+    pub fn lowest_rank(&self) -> usize {
+        match self {
+            Deck::Standard => 0,
+            Deck::ShortDeck => 4,
+        }
+    }
+
+    /// カード `card` がデッキに含まれるかどうかを返す
+    #[inline]
+    pub fn contains(&self, card: usize) -> bool {
+        rank_of(card) >= self.lowest_rank()
+    }
+
+    /// 手札の番号 `index` の2枚のカードがともにデッキに含まれるかどうかを返す
+    #[inline]
+    pub fn contains_combo(&self, index: usize) -> bool {
+        let (i, _) = combo_cards(index);
+        self.contains(i)
+    }
+
+    /// デッキに含まれるカードを昇順に返す
+    pub fn cards(&self) -> Vec<usize> {
+        (0..NUM_CARDS).filter(|&card| self.contains(card)).collect()
+    }
+
+    /// デッキのカードの枚数を返す
+    #[inline]
+    pub fn num_cards(&self) -> usize {
+        NUM_CARDS - 4 * self.lowest_rank()
+    }
+
+    /// 2人の手札を除いたカードから選ぶ5枚のボードの個数を返す
+    #[inline]
+    pub fn num_boards(&self) -> usize {
+        let n = self.num_cards() - 4;
+        n * (n - 1) * (n - 2) * (n - 3) * (n - 4) / (5 * 4 * 3 * 2)
+    }
+}
+
+impl std::fmt::Display for Deck {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Deck::Standard => write!(f, "standard"),
+            Deck::ShortDeck => write!(f, "short"),
+        }
+    }
+}
+
+impl std::str::FromStr for Deck {
+    type Err = String;
+
+    /// "standard" または "short" (別名 "6+") を変換する
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "standard" => Ok(Deck::Standard),
+            "short" | "6+" => Ok(Deck::ShortDeck),
+            _ => Err(format!("unknown deck: '{}'", s)),
+        }
+    }
+}
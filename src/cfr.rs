@@ -1,4 +1,5 @@
 use crate::interface::*;
+use crate::strategy::*;
 use std::collections::HashMap;
 
 // ベクトル演算を行う関数群
@@ -39,36 +40,137 @@ fn nonneg_assign_vec(vec: &mut Vec<f64>) {
     vec.iter_mut().for_each(|el| *el = el.max(0.0));
 }
 
+/// CFRアルゴリズムの変種
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SolverVariant {
+    /// 割引を行わない通常の CFR
+    Vanilla,
+
+    /// CFR+ (負のリグレットを0に切り詰め、平均戦略を線形の重みで計算する)
+    CFRPlus,
+
+    /// Discounted CFR (パラメータ `alpha`, `beta`, `gamma` を持つ)
+    Discounted { alpha: f64, beta: f64, gamma: f64 },
+}
+
+impl Default for SolverVariant {
+    fn default() -> Self {
+        SolverVariant::Discounted {
+            alpha: 1.5,
+            beta: 0.0,
+            gamma: 2.0,
+        }
+    }
+}
+
+impl SolverVariant {
+    /// 時刻 `t` における割引のパラメータ `(alpha_t, beta_t, gamma_t)` を返す
+    ///
+    /// 正・負のリグレットの累積値にそれぞれ `alpha_t`, `beta_t` を掛けてから新たなリグレットを
+    /// 加え、時刻 `t` の戦略は `gamma_t` の重みで平均戦略に加える
+    fn discount(&self, t: usize) -> (f64, f64, f64) {
+        let t = t as f64;
+        match *self {
+            SolverVariant::Vanilla => (1.0, 1.0, 1.0),
+            // 負のリグレットを0にしてから加えることは、加えた後に0に切り詰めることと
+            // regret-matching の結果の上で等価である
+            SolverVariant::CFRPlus => (1.0, 0.0, t + 1.0),
+            SolverVariant::Discounted { alpha, beta, gamma } => (
+                t.powf(alpha) / (t.powf(alpha) + 1.0),
+                t.powf(beta) / (t.powf(beta) + 1.0),
+                (t + 1.0).powf(gamma),
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for SolverVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SolverVariant::Vanilla => write!(f, "CFR"),
+            SolverVariant::CFRPlus => write!(f, "CFR+"),
+            SolverVariant::Discounted { alpha, beta, gamma } => write!(
+                f,
+                "Discounted CFR (alpha = {}, beta = {}, gamma = {})",
+                alpha, beta, gamma
+            ),
+        }
+    }
+}
+
+impl std::str::FromStr for SolverVariant {
+    type Err = String;
+
+    /// "cfr", "cfr+", "lcfr", "dcfr", "dcfr:1.5,0,2" のような文字列を変換する
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, params) = match s.split_once(':') {
+            Some((name, params)) => (name, Some(params)),
+            None => (s, None),
+        };
+
+        match (name.to_ascii_lowercase().as_str(), params) {
+            ("cfr", None) => Ok(SolverVariant::Vanilla),
+            ("cfr+", None) => Ok(SolverVariant::CFRPlus),
+            ("lcfr", None) => Ok(SolverVariant::Discounted {
+                alpha: 1.0,
+                beta: 1.0,
+                gamma: 1.0,
+            }),
+            ("dcfr", None) => Ok(SolverVariant::default()),
+            ("dcfr", Some(params)) => {
+                let params = params
+                    .split(',')
+                    .map(|x| x.trim().parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| format!("invalid solver parameters: '{}'", s))?;
+                match params.as_slice() {
+                    &[alpha, beta, gamma] => Ok(SolverVariant::Discounted { alpha, beta, gamma }),
+                    _ => Err(format!("invalid solver parameters: '{}'", s)),
+                }
+            }
+            _ => Err(format!("unknown solver: '{}'", s)),
+        }
+    }
+}
+
 /// CFRアルゴリズムを管理する構造体
+///
+/// ゼロサムでないゲームでは平均戦略がナッシュ均衡に収束する保証はないため、
+/// 結果は `util::compute_exploitability()` (NashConv) で確認する
 pub struct CFRMinimizer<'a, T: Game> {
     /// ゲーム定義のインスタンス
     game: &'a T,
 
+    /// CFRアルゴリズムの変種
+    variant: SolverVariant,
+
     /// リグレットの累積値
     cum_regret: HashMap<PublicHistory, Vec<Vec<f64>>>,
 
     /// 各時刻の戦略の和
     cum_strategy: HashMap<PublicHistory, Vec<Vec<f64>>>,
 
-    /// Discounted CFR のパラメータ
+    /// 正のリグレットの累積値の割引率
     alpha_t: f64,
 
-    /// Discounted CFR のパラメータ
+    /// 負のリグレットの累積値の割引率
     beta_t: f64,
 
-    /// Discounted CFR のパラメータ
+    /// 現時刻の戦略の重み
     gamma_t: f64,
 }
 
 impl<'a, T: 'a + Game> CFRMinimizer<'a, T> {
-    const ALPHA: f64 = 1.5;
-    const BETA: f64 = 0.0;
-    const GAMMA: f64 = 2.0;
-
-    /// コンストラクタ
+    /// コンストラクタ (既定の Discounted CFR を用いる)
     pub fn new(game: &'a T) -> Self {
+        Self::with_variant(game, SolverVariant::default())
+    }
+
+    /// CFRアルゴリズムの変種 `variant` を指定するコンストラクタ
+    pub fn with_variant(game: &'a T, variant: SolverVariant) -> Self {
         Self {
             game,
+            variant,
             cum_regret: HashMap::new(),
             cum_strategy: HashMap::new(),
             alpha_t: 1.0,
@@ -78,31 +180,38 @@ impl<'a, T: 'a + Game> CFRMinimizer<'a, T> {
     }
 
     /// CFRアルゴリズムによる学習を行い、平均戦略を返す
-    pub fn compute(&mut self, num_iterations: i32) -> HashMap<PublicHistory, Vec<Vec<f64>>> {
+    pub fn compute(&mut self, num_iterations: usize) -> Strategy {
         // ゲームの初期履歴を取得
-        let root = T::root();
+        let root = self.game.root();
 
         // ゲーム木を構築して累積値を0で初期化
-        Self::build_tree(&root, &mut self.cum_regret);
-        Self::build_tree(&root, &mut self.cum_strategy);
+        Self::build_tree(self.game, &root, &mut self.cum_regret);
+        Self::build_tree(self.game, &root, &mut self.cum_strategy);
 
-        // 到達確率を1で初期化
-        let ones = vec![1.0; T::num_private_hands()];
+        // 到達確率をゲームが与える初期値で初期化
+        let initial_reach = [self.game.initial_reach(0), self.game.initial_reach(1)];
 
         // 自己対戦を繰り返す
         for t in 0..num_iterations {
-            let t_f64 = t as f64;
-            self.alpha_t = t_f64.powf(Self::ALPHA) / (t_f64.powf(Self::ALPHA) + 1.0);
-            self.beta_t = t_f64.powf(Self::BETA) / (t_f64.powf(Self::BETA) + 1.0);
-            self.gamma_t = (t_f64 + 1.0).powf(Self::GAMMA);
+            (self.alpha_t, self.beta_t, self.gamma_t) = self.variant.discount(t);
 
             // プレイヤー毎に処理を行う
             for player in 0..2 {
-                self.cfr_recursive(&root, player, &ones, &ones);
+                let pi = &initial_reach[player];
+                let pmi = &initial_reach[player ^ 1];
+                self.cfr_recursive(&root, player, pi, pmi);
             }
         }
 
-        self.compute_average_strategy()
+        let metadata = StrategyMetadata {
+            game_name: self.game.name(),
+            parameters: self.game.parameters(),
+            num_iterations,
+            exploitability: None,
+            solver: self.variant.to_string(),
+        };
+
+        Strategy::new(self.compute_average_strategy(), metadata)
     }
 
     /// `player` の counterfactual value を再帰的に計算する
@@ -122,10 +231,10 @@ impl<'a, T: 'a + Game> CFRMinimizer<'a, T> {
         let public_history = node.public_history();
 
         // 現時刻の戦略を regret-matching アルゴリズムによって求める
-        let mut strategy = Self::regret_matching(&self.cum_regret[public_history]);
+        let mut strategy = self.regret_matching(&self.cum_regret[public_history]);
 
         // 返り値となる counterfactual value を0で初期化
-        let mut cfvalue = vec![0.0; T::num_private_hands()];
+        let mut cfvalue = vec![0.0; self.game.num_private_hands()];
 
         // 手番が `player` の場合
         if node.current_player() == player {
@@ -178,25 +287,25 @@ impl<'a, T: 'a + Game> CFRMinimizer<'a, T> {
     }
 
     /// ゲーム木を構築する
-    fn build_tree(node: &T::Node, tree: &mut HashMap<PublicHistory, Vec<Vec<f64>>>) {
+    fn build_tree(game: &T, node: &T::Node, tree: &mut HashMap<PublicHistory, Vec<Vec<f64>>>) {
         if node.is_terminal() {
             return;
         }
 
         tree.insert(
             node.public_history().clone(),
-            vec![vec![0.0; T::num_private_hands()]; node.num_actions()],
+            vec![vec![0.0; game.num_private_hands()]; node.num_actions()],
         );
 
         for action in node.actions() {
-            Self::build_tree(&node.play(action), tree);
+            Self::build_tree(game, &node.play(action), tree);
         }
     }
 
     /// regret-matching アルゴリズム
-    fn regret_matching(regrets: &Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    fn regret_matching(&self, regrets: &Vec<Vec<f64>>) -> Vec<Vec<f64>> {
         let num_actions = regrets.len();
-        let num_private_hands = T::num_private_hands();
+        let num_private_hands = self.game.num_private_hands();
         let mut strategy = regrets.clone();
 
         let mut denom = vec![0.0; num_private_hands];
@@ -214,7 +323,7 @@ impl<'a, T: 'a + Game> CFRMinimizer<'a, T> {
 
     /// フィールド `cum_strategy` を参照して平均戦略を返す
     fn compute_average_strategy(&self) -> HashMap<PublicHistory, Vec<Vec<f64>>> {
-        let num_private_hands = T::num_private_hands();
+        let num_private_hands = self.game.num_private_hands();
         let mut average_strategy = self.cum_strategy.clone();
 
         for strategy in average_strategy.values_mut() {
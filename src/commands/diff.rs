@@ -0,0 +1,108 @@
+use super::report::Report;
+use super::*;
+use yabai_vol7_src::cards::NUM_COMBOS;
+use yabai_vol7_src::diff::*;
+use yabai_vol7_src::grid::*;
+use yabai_vol7_src::strategy::*;
+
+const USAGE: &str = "usage: yabai diff <STRATEGY_A> <STRATEGY_B> [--min-delta <X>] \
+                     [--table <PATH>] [--short-deck-table <PATH>] \
+                     [--tie-table <PATH>] [--short-deck-tie-table <PATH>] [--plo-table <PATH>] [--color]";
+
+/// 2つの戦略の差分を出力する
+fn print_diff<T: Report>(
+    game_a: &T,
+    a: &Strategy,
+    b: &Strategy,
+    min_delta: f64,
+    color: bool,
+) -> Result<(), String> {
+    let game_b = T::from_parameters(&b.metadata.parameters)?;
+    if game_a.num_private_hands() != game_b.num_private_hands() {
+        return Err("cannot compare strategies with different numbers of hands".to_string());
+    }
+    let diff = diff_strategies(game_a, a, &game_b, b);
+    let render = |grid: &HandGrid, format| match color {
+        true => grid.to_ansi(format),
+        false => grid.to_ascii(format),
+    };
+
+    println!();
+    println!("[{}]", a.metadata.game_name);
+    for (name, strategy) in [("A", a), ("B", b)] {
+        let parameters = strategy
+            .metadata
+            .parameters
+            .iter()
+            .map(|(k, v)| format!("{} = {}", k, v))
+            .collect::<Vec<_>>();
+        println!(
+            "- {}: {} ({}, {} iterations)",
+            name,
+            parameters.join(", "),
+            strategy.metadata.solver,
+            strategy.metadata.num_iterations
+        );
+    }
+    print!("{}", diff.summary());
+
+    // プッシュ/フォールドのように 2枚の手札を持つゲームではグリッドで表示する
+    if game_a.num_private_hands() == NUM_COMBOS {
+        for infoset in &diff.infosets {
+            let action = infoset.actions.len() - 1;
+            println!();
+            println!("[{}: {}% (B - A)]", infoset.label, infoset.actions[action]);
+            let grid = HandGrid::from_combos(&infoset.deltas(action));
+            print!("{}", render(&grid, CellFormat::PercentDiff));
+        }
+        for player in 0..2 {
+            println!();
+            println!("[EV of player {} (B - A)]", player);
+            let grid = HandGrid::from_combos(&diff.hand_ev_delta(player));
+            print!("{}", render(&grid, CellFormat::Signed));
+        }
+    } else {
+        println!();
+        print!("{}", diff.to_table(min_delta));
+    }
+
+    Ok(())
+}
+
+/// `yabai diff`: 保存された2つの戦略を比較する
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &[
+            "min-delta",
+            "table",
+            "short-deck-table",
+            "tie-table",
+            "short-deck-tie-table",
+            "plo-table",
+        ],
+        &["color"],
+    )?;
+    if args.num_positional() != 2 {
+        return Err(USAGE.to_string());
+    }
+    apply_table_path(&args)?;
+
+    let a = load_strategy(args.positional(0, "STRATEGY_A")?)?;
+    let b = load_strategy(args.positional(1, "STRATEGY_B")?)?;
+    let min_delta = args.get_or("min-delta", 0.0)?;
+    if a.metadata.game_name != b.metadata.game_name {
+        return Err(format!(
+            "cannot compare strategies of different games: '{}' and '{}'",
+            a.metadata.game_name, b.metadata.game_name
+        ));
+    }
+
+    with_game!(a.metadata, |game| print_diff(
+        &game,
+        &a,
+        &b,
+        min_delta,
+        args.flag("color")
+    ))
+}
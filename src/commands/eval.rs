@@ -0,0 +1,60 @@
+use super::*;
+use yabai_vol7_src::interface::*;
+use yabai_vol7_src::strategy::*;
+use yabai_vol7_src::util::*;
+
+const USAGE: &str = "usage: yabai eval <STRATEGY> [--table <PATH>] [--short-deck-table <PATH>] \
+                     [--tie-table <PATH>] [--short-deck-tie-table <PATH>] [--plo-table <PATH>]";
+
+/// 戦略の組 `strategy` の期待値と可搾取量を出力する
+fn print_eval<T: Game>(game: &T, strategy: &Strategy) {
+    let meta = &strategy.metadata;
+    println!();
+    println!("[{}]", game.name());
+    for (name, value) in &meta.parameters {
+        println!("- {}: {}", name, value);
+    }
+    println!("- Solver: {}", meta.solver);
+    println!("- Iterations: {}", meta.num_iterations);
+    if let Some(exploitability) = meta.exploitability {
+        println!("- Recorded exploitability: {:+.3e}", exploitability);
+    }
+    println!(
+        "- Exploitability: {:+.3e}",
+        compute_exploitability(game, strategy)
+    );
+    for player in 0..2 {
+        println!(
+            "- EV of player {}: {:+.4}",
+            player,
+            compute_ev(game, player, strategy)
+        );
+    }
+}
+
+/// `yabai eval`: 保存された戦略の期待値と可搾取量を計算する
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &[
+            "table",
+            "short-deck-table",
+            "tie-table",
+            "short-deck-tie-table",
+            "plo-table",
+        ],
+        &[],
+    )?;
+    if args.num_positional() != 1 {
+        return Err(USAGE.to_string());
+    }
+    apply_table_path(&args)?;
+
+    let path = args.positional(0, "STRATEGY")?;
+    let strategy = load_strategy(path)?;
+
+    with_game!(strategy.metadata, |game| {
+        print_eval(&game, &strategy);
+        Ok(())
+    })
+}
@@ -0,0 +1,82 @@
+use super::report::*;
+use super::*;
+use yabai_vol7_src::export::*;
+use yabai_vol7_src::grid::*;
+use yabai_vol7_src::strategy::*;
+
+const USAGE: &str = "usage: yabai export <STRATEGY> [--format text|json|csv|html|svg] \
+                     [--output <PATH>] [--table <PATH>] [--short-deck-table <PATH>] \
+                     [--tie-table <PATH>] [--short-deck-tie-table <PATH>] [--plo-table <PATH>] [--color]";
+
+/// 戦略の組 `strategy` を形式 `format` の文字列に変換する
+pub fn render<T: Report>(
+    game: &T,
+    strategy: &Strategy,
+    format: &str,
+    color: bool,
+) -> Result<String, String> {
+    let mut buf = Vec::new();
+    match format {
+        "text" => return Ok(game.report(strategy, color)),
+        "json" => write_json(&mut buf, game, strategy).map_err(|e| e.to_string())?,
+        "csv" => write_csv(&mut buf, game, strategy).map_err(|e| e.to_string())?,
+        "html" | "svg" => {
+            let grids = game
+                .grids(strategy)
+                .ok_or_else(|| format!("format '{}' is not supported by this game", format))?;
+            let ret = match format {
+                "html" => {
+                    let tables = grids
+                        .iter()
+                        .map(|(title, grid)| grid.to_html(title, CellFormat::Percent))
+                        .collect::<String>();
+                    format!(
+                        "<!DOCTYPE html>\n<html>\n<body>\n{}</body>\n</html>\n",
+                        tables
+                    )
+                }
+                _ => {
+                    let grids = grids
+                        .iter()
+                        .map(|(title, grid)| (title.as_str(), grid))
+                        .collect::<Vec<_>>();
+                    grids_to_svg(&grids, CellFormat::Percent)
+                }
+            };
+            return Ok(ret);
+        }
+        _ => return Err(format!("unknown format: '{}'", format)),
+    }
+    Ok(String::from_utf8(buf).unwrap())
+}
+
+/// `yabai export`: 保存された戦略を指定された形式で書き出す
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &[
+            "format",
+            "output",
+            "table",
+            "short-deck-table",
+            "tie-table",
+            "short-deck-tie-table",
+            "plo-table",
+        ],
+        &["color"],
+    )?;
+    if args.num_positional() != 1 {
+        return Err(USAGE.to_string());
+    }
+    apply_table_path(&args)?;
+
+    let path = args.positional(0, "STRATEGY")?;
+    let strategy = load_strategy(path)?;
+    let format = args.get_or("format", "text".to_string())?;
+    let output = args.get::<String>("output")?;
+
+    with_game!(strategy.metadata, |game| {
+        let contents = render(&game, &strategy, &format, args.flag("color"))?;
+        write_output(output.as_deref(), &contents)
+    })
+}
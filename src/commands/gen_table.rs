@@ -0,0 +1,482 @@
+use super::*;
+use holdem_hand_evaluator::{heads_up_win_frequency, Hand};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::time::Instant;
+use yabai_vol7_src::cards::*;
+use yabai_vol7_src::equity_table::{compress, decompress, is_compressed};
+use yabai_vol7_src::evaluator::{evaluate_hand, evaluate_omaha};
+use yabai_vol7_src::game_plo::*;
+use yabai_vol7_src::game_push_fold::{
+    DEFAULT_SHORT_DECK_TABLE_PATH, DEFAULT_SHORT_DECK_TIE_TABLE_PATH, DEFAULT_TABLE_PATH,
+    DEFAULT_TIE_TABLE_PATH,
+};
+use yabai_vol7_src::rng::Rng;
+
+const USAGE: &str =
+    "usage: yabai gen-table [--deck standard|short] [--plo | --ties] [--samples <N>] \
+     [--max-error <X>] [--from <PATH>] [--legacy] [--output <PATH>]";
+
+/// 途中経過のファイルの先頭に置かれる識別子
+const PARTIAL_MAGIC: [u8; 4] = *b"YBPT";
+
+/// 途中経過のファイルのヘッダの大きさ (識別子・デッキとテーブルの種類・代表の組の個数)
+const PARTIAL_HEADER_LEN: usize = 12;
+
+/// 1度に計算して途中経過のファイルに追記する代表の組の個数
+const BLOCK_SIZE: usize = 1000;
+
+/// PLO の勝率テーブルの生成において、標準誤差を確認する間隔 (最小の試行回数でもある)
+const PLO_CHECK_INTERVAL: usize = 100;
+
+/// 手札の組毎に数える値の種類
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TableKind {
+    /// `2 * 勝ち + 引き分け` の回数 (勝率テーブル)
+    Equity = 0,
+
+    /// 引き分けの回数 (バウンティ付きのゲームで勝ちと引き分けを区別するのに用いる)
+    Tie = 1,
+}
+
+/// デッキ `deck` の種類 `kind` のテーブルを計算して `path` に書き出す
+///
+/// 計算済みの値は `<path>.partial` に追記され、中断した場合は次回の実行時に続きから再開する
+fn generate(path: &str, deck: Deck, kind: TableKind, legacy: bool) -> Result<(), String> {
+    let partial = format!("{}.partial", path);
+    let result = isomorphic_table(deck, kind, &partial)?;
+    check_table(&result, deck, kind)?;
+    write_table(path, &result, legacy)?;
+    std::fs::remove_file(&partial).map_err(|e| format!("could not remove '{}': {}", partial, e))
+}
+
+/// 既存の種類 `kind` のテーブル `from` (どちらの形式でもよい) を読み込んで `path` に書き出す
+fn convert(
+    from: &str,
+    path: &str,
+    deck: Deck,
+    kind: TableKind,
+    legacy: bool,
+) -> Result<(), String> {
+    let bytes = std::fs::read(from).map_err(|e| format!("could not read '{}': {}", from, e))?;
+    let table = match is_compressed(&bytes) {
+        true => decompress(&bytes),
+        false => bincode::deserialize::<Vec<i32>>(&bytes).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| format!("invalid table '{}': {}", from, e))?;
+    check_table(&table, deck, kind)?;
+    write_table(path, &table, legacy)
+}
+
+/// デッキ `deck` の種類 `kind` のテーブル `table` の大きさと値を確認する
+///
+/// 勝率テーブルでは値の総和を、引き分けの回数のテーブルでは手札の入れ替えについての対称性を確認する
+fn check_table(table: &[i32], deck: Deck, kind: TableKind) -> Result<(), String> {
+    if table.len() != NUM_COMBOS * NUM_COMBOS {
+        return Err(format!("invalid table length: {}", table.len()));
+    }
+    if kind == TableKind::Tie {
+        let symmetric = (0..NUM_COMBOS)
+            .all(|a| (0..a).all(|b| table[a * NUM_COMBOS + b] == table[b * NUM_COMBOS + a]));
+        return match symmetric {
+            true => Ok(()),
+            false => Err("tie table is not symmetric".to_string()),
+        };
+    }
+
+    // 手札の組 (a, b) と (b, a) の値の和は常にボードの個数の2倍となる
+    let n = deck.num_cards();
+    let expected = ((n * (n - 1) / 2) * ((n - 2) * (n - 3) / 2) * deck.num_boards()) as u64;
+    let sum = table.iter().map(|x| *x as u64).sum::<u64>();
+    match sum == expected {
+        true => Ok(()),
+        false => Err(format!(
+            "table sum mismatch: expected {}, got {}",
+            expected, sum
+        )),
+    }
+}
+
+/// 勝率テーブル `table` を `path` に書き出す (`legacy` が真の場合は bincode による形式)
+///
+/// 書き出しの途中で中断しても `path` が壊れないよう、一時ファイルに書き出してから名前を変更する
+fn write_table(path: &str, table: &[i32], legacy: bool) -> Result<(), String> {
+    let encoded = match legacy {
+        true => bincode::serialize(table).unwrap(),
+        false => compress(table)?,
+    };
+    let temporary = format!("{}.tmp", path);
+    std::fs::write(&temporary, encoded)
+        .and_then(|_| std::fs::rename(&temporary, path))
+        .map_err(|e| format!("could not write '{}': {}", path, e))
+}
+
+/// 代表の組 `representatives` の値を `BLOCK_SIZE` 個ずつ計算し、進捗を表示しながら `partial` に追記する
+///
+/// `partial` が既に存在する場合は、計算済みの値を読み込んでその続きから計算する
+fn compute_values(
+    representatives: &[(usize, usize)],
+    deck: Deck,
+    kind: TableKind,
+    partial: &str,
+) -> Result<Vec<i32>, String> {
+    let io_error = |e: std::io::Error| format!("could not write '{}': {}", partial, e);
+    let mut header = Vec::with_capacity(PARTIAL_HEADER_LEN);
+    header.extend(PARTIAL_MAGIC);
+    // 勝率テーブルの途中経過のファイルとの互換性のため、種類はデッキと同じ語の上位に置く
+    header.extend((deck as u32 | (kind as u32) << 16).to_le_bytes());
+    header.extend((representatives.len() as u32).to_le_bytes());
+
+    let mut values = Vec::with_capacity(representatives.len());
+    let existing = match File::open(partial) {
+        Ok(mut file) => {
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes).map_err(io_error)?;
+            Some(bytes)
+        }
+        Err(_) => None,
+    };
+    let mut file = match existing {
+        // ヘッダの書き込みの途中で中断した場合は、計算済みの値がないため最初からやり直す
+        Some(bytes) if bytes.len() < PARTIAL_HEADER_LEN && header.starts_with(&bytes) => {
+            eprintln!("'{}' has an incomplete header; starting over", partial);
+            create_partial(partial, &header).map_err(io_error)?
+        }
+        Some(bytes) => {
+            if !bytes.starts_with(&PARTIAL_MAGIC) {
+                return Err(format!(
+                    "'{}' is not a partial table file; remove it to start over",
+                    partial
+                ));
+            }
+            if !bytes.starts_with(&header) {
+                return Err(format!(
+                    "'{}' was created with different settings; remove it to start over",
+                    partial
+                ));
+            }
+
+            // 書き込みの途中で中断した値は捨てる
+            let num_values = ((bytes.len() - PARTIAL_HEADER_LEN) / 4).min(representatives.len());
+            let end = PARTIAL_HEADER_LEN + 4 * num_values;
+            values.extend(
+                bytes[PARTIAL_HEADER_LEN..end]
+                    .chunks(4)
+                    .map(|chunk| i32::from_le_bytes(chunk.try_into().unwrap())),
+            );
+            let file = OpenOptions::new()
+                .write(true)
+                .open(partial)
+                .map_err(io_error)?;
+            file.set_len(end as u64).map_err(io_error)?;
+            eprintln!(
+                "resuming from '{}' ({} / {} pairs)",
+                partial,
+                num_values,
+                representatives.len()
+            );
+            OpenOptions::new()
+                .append(true)
+                .open(partial)
+                .map_err(io_error)?
+        }
+        None => create_partial(partial, &header).map_err(io_error)?,
+    };
+
+    let start = Instant::now();
+    let num_resumed = values.len();
+    while values.len() < representatives.len() {
+        let end = (values.len() + BLOCK_SIZE).min(representatives.len());
+        let block = representatives[values.len()..end]
+            .par_iter()
+            .map(|&(a, b)| {
+                let (win, tie) = board_counts(a, b, deck);
+                match kind {
+                    TableKind::Equity => 2 * win + tie,
+                    TableKind::Tie => tie,
+                }
+            })
+            .collect::<Vec<_>>();
+        let bytes = block
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect::<Vec<_>>();
+        file.write_all(&bytes).map_err(io_error)?;
+        file.sync_data().map_err(io_error)?;
+        values.extend(block);
+
+        // 今回の実行で計算した値の個数と経過時間から残り時間を見積もる
+        let elapsed = start.elapsed().as_secs_f64();
+        let remaining = (representatives.len() - values.len()) as f64;
+        let eta = elapsed / (values.len() - num_resumed) as f64 * remaining;
+        eprintln!(
+            "{} / {} pairs ({:.1}%), elapsed {}, ETA {}",
+            values.len(),
+            representatives.len(),
+            100.0 * values.len() as f64 / representatives.len() as f64,
+            format_duration(elapsed),
+            format_duration(eta)
+        );
+    }
+
+    Ok(values)
+}
+
+/// 途中経過のファイル `partial` を作成してヘッダ `header` を書き込む
+fn create_partial(partial: &str, header: &[u8]) -> std::io::Result<File> {
+    let mut file = File::create(partial)?;
+    file.write_all(header)?;
+    file.sync_data()?;
+    Ok(file)
+}
+
+/// 秒数 `seconds` を "1:02:03" のような文字列に変換する
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// デッキ `deck` の種類 `kind` のテーブルを計算する
+///
+/// スートの入れ替えと手札の入れ替えで移り合う手札の組は、代表の組についてのみ計算する
+/// (手札の組 (a, b) と (b, a) の値の和は、勝率テーブルではボードの個数の2倍となり、
+/// 引き分けの回数のテーブルでは等しい)
+///
+/// 代表の組の値は `partial` を途中経過のファイルとして計算する (`compute_values()` を参照)
+fn isomorphic_table(deck: Deck, kind: TableKind, partial: &str) -> Result<Vec<i32>, String> {
+    let combos = (0..NUM_COMBOS)
+        .filter(|&combo| deck.contains_combo(combo))
+        .collect::<Vec<_>>();
+    let num_boards = deck.num_boards() as i32;
+
+    // 手札の組の代表と、代表が手札を入れ替えたものかどうかを返す
+    let permuted = suit_permutations()
+        .iter()
+        .map(|perm| {
+            (0..NUM_COMBOS)
+                .map(|combo| permute_combo(combo, perm))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let canonical = |a: usize, b: usize| {
+        permuted
+            .iter()
+            .flat_map(|perm| {
+                let (a, b) = (perm[a], perm[b]);
+                [((a, b), false), ((b, a), true)]
+            })
+            .min()
+            .unwrap()
+    };
+
+    let mut representatives = combos
+        .par_iter()
+        .flat_map_iter(|&a| {
+            combos
+                .iter()
+                .filter(move |&&b| combos_disjoint(a, b))
+                .map(move |&b| canonical(a, b).0)
+        })
+        .collect::<Vec<_>>();
+    representatives.par_sort_unstable();
+    representatives.dedup();
+
+    let values = compute_values(&representatives, deck, kind, partial)?;
+    let values = representatives
+        .into_iter()
+        .zip(values)
+        .collect::<HashMap<_, _>>();
+
+    let mut result = vec![0; NUM_COMBOS * NUM_COMBOS];
+    result
+        .par_chunks_mut(NUM_COMBOS)
+        .enumerate()
+        .filter(|(a, _)| deck.contains_combo(*a))
+        .for_each(|(a, row)| {
+            for &b in combos.iter().filter(|&&b| combos_disjoint(a, b)) {
+                let (representative, swapped) = canonical(a, b);
+                let value = values[&representative];
+                row[b] = match (swapped, kind) {
+                    (true, TableKind::Equity) => 2 * num_boards - value,
+                    _ => value,
+                };
+            }
+        });
+    Ok(result)
+}
+
+/// デッキ `deck` の全てのボードについて、手札 `a` の手札 `b` に対する勝ちと引き分けの回数を数える
+fn board_counts(a: usize, b: usize, deck: Deck) -> (i32, i32) {
+    let (i, j) = combo_cards(a);
+    let (m, n) = combo_cards(b);
+
+    // 通常のデッキでは高速な `heads_up_win_frequency` を用いる
+    if deck == Deck::Standard {
+        let empty = Hand::new();
+        let (hand_a, hand_b) = (Hand::from_slice(&[i, j]), Hand::from_slice(&[m, n]));
+        let (win, _, tie) = heads_up_win_frequency(&hand_a, &hand_b, &empty, &empty);
+        return (win as i32, tie as i32);
+    }
+
+    let (mask_a, mask_b) = ((1u64 << i) | (1u64 << j), (1u64 << m) | (1u64 << n));
+    let cards = deck
+        .cards()
+        .into_iter()
+        .filter(|&card| (mask_a | mask_b) & (1 << card) == 0)
+        .collect::<Vec<_>>();
+
+    let n = cards.len();
+    let (mut win, mut tie) = (0, 0);
+    for c1 in 0..n {
+        let b1 = 1u64 << cards[c1];
+        for c2 in (c1 + 1)..n {
+            let b2 = b1 | 1 << cards[c2];
+            for c3 in (c2 + 1)..n {
+                let b3 = b2 | 1 << cards[c3];
+                for c4 in (c3 + 1)..n {
+                    let b4 = b3 | 1 << cards[c4];
+                    for &card in &cards[(c4 + 1)..] {
+                        let board = b4 | 1 << card;
+                        let rank_a = evaluate_hand(board | mask_a, deck);
+                        let rank_b = evaluate_hand(board | mask_b, deck);
+                        match rank_a.cmp(&rank_b) {
+                            std::cmp::Ordering::Greater => win += 1,
+                            std::cmp::Ordering::Equal => tie += 1,
+                            std::cmp::Ordering::Less => {}
+                        }
+                    }
+                }
+            }
+        }
+    }
+    (win, tie)
+}
+
+/// PLO の手札の同値類の組毎の勝率テーブルを計算して `path` に書き出す
+///
+/// 勝率は同値類の組 `(a, b)` 毎に、`a` の代表と `b` の手札のうちそれと重複しないものおよびボードを
+/// 無作為に選んで平均する (スートの対称性から、`a` の手札を代表に固定しても分布は変わらない。
+/// 同じ同値類の組の勝率は 0.5 とする)。
+/// 選ぶ回数は最大で `samples` 回とし、`PLO_CHECK_INTERVAL` 回毎に見積もった標準誤差が
+/// `max_error` 以下になった時点で打ち切る。
+/// テーブルは同値類の組 (`a <= b` の昇順、約 1 億 3500 万組) 毎に `f32` を並べたもの (約 540MB) となる。
+/// 組の数が多いため、既定の設定でも計算には多数のコアで長時間を要する
+fn generate_plo(path: &str, samples: usize, max_error: f64) -> std::io::Result<()> {
+    let result: Vec<f32> = (0..NUM_PLO_CLASSES)
+        .into_par_iter()
+        .flat_map_iter(|a| (a..NUM_PLO_CLASSES).map(move |b| (a, b)))
+        .map(|(a, b)| {
+            let x = plo_class_combos(a)[0];
+            let combos_b = plo_class_combos(b)
+                .iter()
+                .copied()
+                .filter(|&y| x & y == 0)
+                .collect::<Vec<_>>();
+            if a == b || combos_b.is_empty() {
+                return 0.5;
+            }
+
+            let mut rng = Rng::new((a * NUM_PLO_CLASSES + b) as u64);
+            let (mut sum, mut n) = (0.0, 0);
+            while n < samples {
+                let y = combos_b[rng.gen_range(combos_b.len())];
+                let mut used = x | y;
+                let mut board = 0;
+                for _ in 0..5 {
+                    let card = loop {
+                        let card = rng.gen_range(NUM_CARDS);
+                        if used & (1 << card) == 0 {
+                            break card;
+                        }
+                    };
+                    used |= 1 << card;
+                    board |= 1 << card;
+                }
+
+                let rank_x = evaluate_omaha(x, board, Deck::Standard);
+                let rank_y = evaluate_omaha(y, board, Deck::Standard);
+                sum += match rank_x.cmp(&rank_y) {
+                    std::cmp::Ordering::Greater => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Less => 0.0,
+                };
+                n += 1;
+
+                // 値が [0, 1] に収まるため分散は `mean * (1 - mean)` 以下であり、これを用いて
+                // 標準誤差を上から見積もる (全て同じ結果でも0とならないよう平均を補正する)
+                if n % PLO_CHECK_INTERVAL == 0 {
+                    let mean = (sum + 0.5) / (n + 1) as f64;
+                    if (mean * (1.0 - mean) / n as f64).sqrt() <= max_error {
+                        break;
+                    }
+                }
+            }
+            (sum / n as f64) as f32
+        })
+        .collect();
+    assert_eq!(result.len(), NUM_PLO_CLASS_PAIRS);
+
+    let encoded = bincode::serialize(&result).unwrap();
+    let mut outfile = File::create(path)?;
+    outfile.write_all(&encoded)?;
+
+    Ok(())
+}
+
+/// `yabai gen-table`: プッシュ/フォールドで用いる勝率テーブルを生成する
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &["deck", "output", "samples", "max-error", "from"],
+        &["plo", "ties", "legacy"],
+    )?;
+    if args.num_positional() != 0 {
+        return Err(USAGE.to_string());
+    }
+    let deck = args.get_or("deck", Deck::Standard)?;
+
+    if args.flag("plo") && args.flag("ties") {
+        return Err("'--plo' and '--ties' cannot be combined".to_string());
+    }
+
+    if args.flag("plo") {
+        if deck != Deck::Standard {
+            return Err("the PLO table supports only the heads-up standard deck".to_string());
+        }
+        let samples = args.get_or("samples", 2500)?;
+        if samples == 0 {
+            return Err("'--samples' must be positive".to_string());
+        }
+        let max_error: f64 = args.get_or("max-error", 0.01)?;
+        if max_error.is_nan() || max_error < 0.0 {
+            return Err("'--max-error' must be non-negative".to_string());
+        }
+        let path = args.get_or("output", DEFAULT_PLO_TABLE_PATH.to_string())?;
+        return generate_plo(&path, samples, max_error)
+            .map_err(|e| format!("could not write '{}': {}", path, e));
+    }
+
+    let kind = match args.flag("ties") {
+        true => TableKind::Tie,
+        false => TableKind::Equity,
+    };
+    let default_path = match (deck, kind) {
+        (Deck::Standard, TableKind::Equity) => DEFAULT_TABLE_PATH,
+        (Deck::ShortDeck, TableKind::Equity) => DEFAULT_SHORT_DECK_TABLE_PATH,
+        (Deck::Standard, TableKind::Tie) => DEFAULT_TIE_TABLE_PATH,
+        (Deck::ShortDeck, TableKind::Tie) => DEFAULT_SHORT_DECK_TIE_TABLE_PATH,
+    };
+    let path = args.get_or("output", default_path.to_string())?;
+    let legacy = args.flag("legacy");
+    match args.get::<String>("from")? {
+        Some(from) => convert(&from, &path, deck, kind, legacy),
+        None => generate(&path, deck, kind, legacy),
+    }
+}
@@ -0,0 +1,266 @@
+pub mod diff;
+pub mod eval;
+pub mod export;
+pub mod gen_table;
+pub mod report;
+pub mod simulate;
+pub mod solve;
+pub mod sweep;
+
+use std::str::FromStr;
+use yabai_vol7_src::game_push_fold::{BlindStructure, BountyConfig, RakeConfig};
+use yabai_vol7_src::game_tree::parse_sizes;
+use yabai_vol7_src::icm::IcmConfig;
+use yabai_vol7_src::strategy::Strategy;
+
+/// 保存された戦略の付加情報からゲームを復元し、`$game` に束縛して `$body` を評価する
+macro_rules! with_game {
+    ($metadata:expr, |$game:ident| $body:expr) => {{
+        let metadata = &$metadata;
+        let parameters = &metadata.parameters;
+        match metadata.game_name.as_str() {
+            yabai_vol7_src::game_kuhn::GAME_NAME => {
+                let $game = yabai_vol7_src::game_kuhn::KuhnGame::from_parameters(parameters)?;
+                $body
+            }
+            yabai_vol7_src::game_limp::GAME_NAME => {
+                let $game = yabai_vol7_src::game_limp::LimpGame::from_parameters(parameters)?;
+                $body
+            }
+            yabai_vol7_src::game_plo::GAME_NAME => {
+                let $game = yabai_vol7_src::game_plo::PloGame::from_parameters(parameters)?;
+                $body
+            }
+            yabai_vol7_src::game_push_fold::GAME_NAME => {
+                let $game =
+                    yabai_vol7_src::game_push_fold::PushFoldGame::from_parameters(parameters)?;
+                $body
+            }
+            yabai_vol7_src::game_raise::GAME_NAME => {
+                let $game = yabai_vol7_src::game_raise::RaiseGame::from_parameters(parameters)?;
+                $body
+            }
+            yabai_vol7_src::game_tree::GAME_NAME => {
+                let $game = yabai_vol7_src::game_tree::TreeGame::from_parameters(parameters)?;
+                $body
+            }
+            name => Err(format!("unknown game: '{}'", name)),
+        }
+    }};
+}
+
+pub(crate) use with_game;
+
+/// コマンドライン引数を表す構造体
+pub struct Args {
+    /// 位置引数
+    positional: Vec<String>,
+
+    /// `--name value` 形式のオプション
+    options: Vec<(String, String)>,
+
+    /// `--name` 形式のフラグ
+    flags: Vec<String>,
+}
+
+impl Args {
+    /// 引数の列 `args` を解析する
+    ///
+    /// `options` は値を取るオプションの名前、`flags` は値を取らないフラグの名前の一覧で、
+    /// それ以外の `--` で始まる引数はエラーとなる
+    pub fn parse(args: &[String], options: &[&str], flags: &[&str]) -> Result<Self, String> {
+        let mut ret = Self {
+            positional: Vec::new(),
+            options: Vec::new(),
+            flags: Vec::new(),
+        };
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                ret.positional.push(arg.clone());
+                continue;
+            };
+
+            let (name, value) = match name.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (name, None),
+            };
+
+            if options.contains(&name) {
+                let value = match value {
+                    Some(value) => value,
+                    None => iter
+                        .next()
+                        .ok_or_else(|| format!("option '--{}' requires a value", name))?
+                        .clone(),
+                };
+                ret.options.push((name.to_string(), value));
+            } else if flags.contains(&name) && value.is_none() {
+                ret.flags.push(name.to_string());
+            } else {
+                return Err(format!("unknown option: '{}'", arg));
+            }
+        }
+
+        Ok(ret)
+    }
+
+    /// `index` 番目の位置引数を返す
+    pub fn positional(&self, index: usize, name: &str) -> Result<&str, String> {
+        self.positional
+            .get(index)
+            .map(|s| s.as_str())
+            .ok_or_else(|| format!("missing argument <{}>", name))
+    }
+
+    /// 位置引数の個数を返す
+    pub fn num_positional(&self) -> usize {
+        self.positional.len()
+    }
+
+    /// オプション `name` の値を返す (指定されていなければ `None`)
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        match self.options.iter().rev().find(|(k, _)| k == name) {
+            Some((_, value)) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("invalid value for '--{}': '{}'", name, value)),
+            None => Ok(None),
+        }
+    }
+
+    /// オプション `name` の値を返す (指定されていなければ `default`)
+    pub fn get_or<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        Ok(self.get(name)?.unwrap_or(default))
+    }
+
+    /// フラグ `name` が指定されたかどうかを返す
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+}
+
+/// `path` から戦略を読み込む
+pub fn load_strategy(path: &str) -> Result<Strategy, String> {
+    Strategy::load(path).map_err(|e| format!("could not load '{}': {}", path, e))
+}
+
+/// 勝率テーブルのパス (`--table`, `--short-deck-table`, `--tie-table`, `--short-deck-tie-table`,
+/// `--plo-table`) が指定されていれば設定する
+pub fn apply_table_path(args: &Args) -> Result<(), String> {
+    if let Some(path) = args.get::<String>("table")? {
+        yabai_vol7_src::game_push_fold::set_table_path(&path);
+    }
+    if let Some(path) = args.get::<String>("short-deck-table")? {
+        yabai_vol7_src::game_push_fold::set_short_deck_table_path(&path);
+    }
+    if let Some(path) = args.get::<String>("tie-table")? {
+        yabai_vol7_src::game_push_fold::set_tie_table_path(&path);
+    }
+    if let Some(path) = args.get::<String>("short-deck-tie-table")? {
+        yabai_vol7_src::game_push_fold::set_short_deck_tie_table_path(&path);
+    }
+    if let Some(path) = args.get::<String>("plo-table")? {
+        yabai_vol7_src::game_plo::set_plo_table_path(&path);
+    }
+    Ok(())
+}
+
+/// `--sb`, `--bb`, `--ante`, `--bb-ante` からブラインドとアンティの構造を読み取る
+pub fn parse_blinds(args: &Args) -> Result<BlindStructure, String> {
+    let default = BlindStructure::default();
+    let blinds = BlindStructure {
+        small_blind: args.get_or("sb", default.small_blind)?,
+        big_blind: args.get_or("bb", default.big_blind)?,
+        ante: args.get_or("ante", default.ante)?,
+        bb_ante: args.get_or("bb-ante", default.bb_ante)?,
+    };
+    if !(0.0 <= blinds.small_blind && blinds.small_blind <= blinds.big_blind) {
+        return Err("small blind must be between 0 and the big blind".to_string());
+    }
+    if blinds.ante < 0.0 || blinds.bb_ante < 0.0 {
+        return Err("antes must be non-negative".to_string());
+    }
+    Ok(blinds)
+}
+
+/// 有効スタック `stack` がブラインドとアンティを支払えるか確認する
+pub fn check_stack(stack: f64, blinds: &BlindStructure) -> Result<(), String> {
+    match stack >= blinds.posted(1) {
+        true => Ok(()),
+        false => Err(format!(
+            "effective stack {} does not cover the blinds and antes ({})",
+            stack, blinds
+        )),
+    }
+}
+
+/// `--payouts`, `--other-stacks` から ICM の設定を読み取る (`--payouts` がなければ `None` を返す)
+pub fn parse_icm(args: &Args) -> Result<Option<IcmConfig>, String> {
+    let payouts = match args.get::<String>("payouts")? {
+        Some(payouts) => parse_sizes(&payouts)?,
+        None => return Ok(None),
+    };
+    let icm = IcmConfig {
+        payouts,
+        other_stacks: parse_sizes(&args.get_or("other-stacks", String::new())?)?,
+    };
+    icm.validate()?;
+    Ok(Some(icm))
+}
+
+/// `--sb-bounty`, `--bb-bounty`, `--bounty-immediate`, `--chips-per-bounty` からバウンティの設定を
+/// 読み取る (バウンティが指定されなければ `None` を返す)
+///
+/// ICM を用いない場合 (`icm` が偽の場合) はチップへの換算に `--chips-per-bounty` が必要である
+pub fn parse_bounty(args: &Args, icm: bool) -> Result<Option<BountyConfig>, String> {
+    let sb_bounty = args.get::<f64>("sb-bounty")?;
+    let bb_bounty = args.get::<f64>("bb-bounty")?;
+    if sb_bounty.is_none() && bb_bounty.is_none() {
+        return Ok(None);
+    }
+    let chips_per_bounty = match (args.get::<f64>("chips-per-bounty")?, icm) {
+        (Some(chips), _) => chips,
+        (None, true) => 0.0,
+        (None, false) => {
+            return Err("'--chips-per-bounty' is required unless '--payouts' is given".to_string())
+        }
+    };
+    let bounty = BountyConfig {
+        bounties: [sb_bounty.unwrap_or(0.0), bb_bounty.unwrap_or(0.0)],
+        immediate_fraction: args.get_or("bounty-immediate", 0.5)?,
+        chips_per_bounty,
+    };
+    bounty.validate()?;
+    Ok(Some(bounty))
+}
+
+/// `--rake`, `--rake-cap`, `--rake-preflop` からレーキの設定を読み取る
+/// (`--rake` がなければ `None` を返す)
+pub fn parse_rake(args: &Args) -> Result<Option<RakeConfig>, String> {
+    let rate = match args.get::<f64>("rake")? {
+        Some(rate) => rate,
+        None => return Ok(None),
+    };
+    let rake = RakeConfig {
+        rate,
+        cap: args.get_or("rake-cap", 0.0)?,
+        no_flop_no_drop: !args.flag("rake-preflop"),
+    };
+    rake.validate()?;
+    Ok(Some(rake))
+}
+
+/// `contents` を `path` に書き出す (`path` が `None` の場合は標準出力に書き出す)
+pub fn write_output(path: Option<&str>, contents: &str) -> Result<(), String> {
+    match path {
+        Some(path) => {
+            std::fs::write(path, contents).map_err(|e| format!("could not write '{}': {}", path, e))
+        }
+        None => {
+            print!("{}", contents);
+            Ok(())
+        }
+    }
+}
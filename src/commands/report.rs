@@ -0,0 +1,445 @@
+use yabai_vol7_src::cards::Deck;
+use yabai_vol7_src::export::*;
+use yabai_vol7_src::game_kuhn::KuhnGame;
+use yabai_vol7_src::game_limp::LimpGame;
+use yabai_vol7_src::game_plo::*;
+use yabai_vol7_src::game_push_fold::PushFoldGame;
+use yabai_vol7_src::game_raise::RaiseGame;
+use yabai_vol7_src::game_tree::*;
+use yabai_vol7_src::grid::*;
+use yabai_vol7_src::interface::*;
+use yabai_vol7_src::range::format_range;
+use yabai_vol7_src::strategy::*;
+use yabai_vol7_src::util::*;
+
+/// 解析結果の出力方法を定めるトレイト
+pub trait Report: Game + Sync + Sized {
+    /// `Game::parameters()` が返すパラメータの列からゲームを復元する
+    fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String>;
+
+    /// 戦略の組 `strategy` の解析結果を人間が読める形式の文字列にする
+    fn report(&self, strategy: &Strategy, color: bool) -> String;
+
+    /// 戦略の組 `strategy` を表すグリッドの一覧を返す (グリッドで表せないゲームでは `None`)
+    fn grids(&self, _strategy: &Strategy) -> Option<Vec<(String, HandGrid)>> {
+        None
+    }
+}
+
+/// 可搾取量を返す (付加情報に記録されていなければ計算する)
+fn exploitability<T: Game>(game: &T, strategy: &Strategy) -> f64 {
+    strategy
+        .metadata
+        .exploitability
+        .unwrap_or_else(|| compute_exploitability(game, strategy))
+}
+
+impl Report for KuhnGame {
+    fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        KuhnGame::from_parameters(parameters)
+    }
+
+    fn report(&self, strategy: &Strategy, _color: bool) -> String {
+        let ev = compute_ev(self, 0, strategy);
+        let mut ret = String::new();
+
+        ret.push_str(&format!("\n[{}]\n", self.name()));
+        ret.push_str(&format!(
+            "- Exploitability: {:+.3e}\n",
+            exploitability(self, strategy)
+        ));
+
+        for (player, player_name, player_ev) in [(0, "First", ev), (1, "Second", -ev)] {
+            ret.push_str(&format!("\n[{} player]\n", player_name));
+            ret.push_str(&format!("- EV: {:+.4}\n", player_ev));
+
+            let mut nodes = Vec::new();
+            collect_nodes(self.root(), &mut nodes);
+            for node in nodes.iter().filter(|n| n.current_player() == player) {
+                let history = node.public_history();
+                let action = node.num_actions() - 1;
+                let history_str = match history.len() {
+                    0 => String::new(),
+                    _ => format!("({} => ?)", history_label(self, history)),
+                };
+                ret.push_str(&format!(
+                    "- {}% {}\n",
+                    node.action_name(action),
+                    history_str
+                ));
+                for hand in (0..self.num_private_hands()).rev() {
+                    ret.push_str(&format!(
+                        "    {}: {:.2}%\n",
+                        self.hand_name(hand),
+                        100.0 * strategy.action_prob(history, action, hand)
+                    ));
+                }
+            }
+        }
+
+        ret
+    }
+}
+
+impl Report for PushFoldGame {
+    fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        PushFoldGame::from_parameters(parameters)
+    }
+
+    fn report(&self, strategy: &Strategy, color: bool) -> String {
+        let ev = compute_ev(self, 0, strategy);
+        // ICM・バウンティ・レーキを用いる場合はゼロサムではないため、ビッグブラインドの利得も
+        // 個別に計算する
+        let ev_bb = match self.is_zero_sum() {
+            true => -ev,
+            false => compute_ev(self, 1, strategy),
+        };
+        let (unit, expected) = match self.icm() {
+            None => ("[bb]", "expected stack"),
+            Some(_) => ("", "expected equity"),
+        };
+        let pusher = &strategy[&vec![]];
+        let caller = &strategy[&vec![1]];
+        // デッキに含まれない手札や初期レンジの外の手札は集計から除く
+        let push_grid = HandGrid::from_combos_weighted(&pusher[1], &self.initial_reach(0));
+        let call_grid = HandGrid::from_combos_weighted(&caller[1], &self.initial_reach(1));
+
+        // レンジ表記では確率を1%単位に丸める
+        let round = |probs: &Vec<f64>| {
+            probs
+                .iter()
+                .map(|p| (100.0 * p).round() / 100.0)
+                .collect::<Vec<_>>()
+        };
+        let render = |grid: &HandGrid| match color {
+            true => grid.to_ansi(CellFormat::Percent),
+            false => grid.to_ascii(CellFormat::Percent),
+        };
+
+        let stacks = self.stacks();
+        let stacks_str = match stacks[0] == stacks[1] {
+            true => String::new(),
+            false => format!("stacks = {}/{}[bb], ", stacks[0], stacks[1]),
+        };
+
+        let mut payoff_str = match self.icm() {
+            None => String::new(),
+            Some(icm) => format!(", ICM: {}", icm),
+        };
+        if let Some(bounty) = self.bounty() {
+            payoff_str.push_str(&format!(", {}", bounty));
+        }
+        if let Some(rake) = self.rake() {
+            payoff_str.push_str(&format!(", {}", rake));
+        }
+        if self.deck() != Deck::Standard {
+            payoff_str.push_str(&format!(", {} deck", self.deck()));
+        }
+
+        let mut ret = String::new();
+        ret.push_str(&format!(
+            "\n[{}] ({}effective stack = {}[bb], {}{})\n",
+            self.name(),
+            stacks_str,
+            self.effective_stack(),
+            self.blinds(),
+            payoff_str
+        ));
+        // ゼロサムでない場合の可搾取量は NashConv (最適応答による利得の増分の和) である
+        ret.push_str(&format!(
+            "- {}: {:+.3e}{}\n",
+            match self.is_zero_sum() {
+                true => "Exploitability",
+                false => "Exploitability (NashConv)",
+            },
+            exploitability(self, strategy),
+            unit
+        ));
+
+        ret.push_str("\n[Pusher (Small blind)]\n");
+        ret.push_str(&format!(
+            "- EV: {:+.4}{} ({} = {:.4}{})\n",
+            ev,
+            unit,
+            expected,
+            self.expected_stack(0, ev),
+            unit
+        ));
+        ret.push_str(&format!(
+            "- Overall push rate: {:.2}%\n",
+            100.0 * push_grid.overall()
+        ));
+        ret.push_str(&format!("- Range: {}\n", format_range(&round(&pusher[1]))));
+        ret.push_str(&render(&push_grid));
+
+        ret.push_str("\n[Caller (Big blind)]\n");
+        ret.push_str(&format!(
+            "- EV = {:+.4}{} ({} = {:.4}{})\n",
+            ev_bb,
+            unit,
+            expected,
+            self.expected_stack(1, ev_bb),
+            unit
+        ));
+        ret.push_str(&format!(
+            "- Overall call rate: {:.2}%\n",
+            100.0 * call_grid.overall()
+        ));
+        ret.push_str(&format!("- Range: {}\n", format_range(&round(&caller[1]))));
+        ret.push_str(&render(&call_grid));
+
+        ret
+    }
+
+    fn grids(&self, strategy: &Strategy) -> Option<Vec<(String, HandGrid)>> {
+        Some(vec![
+            (
+                "Pusher (Small blind)".to_string(),
+                HandGrid::from_combos_weighted(&strategy[&vec![]][1], &self.initial_reach(0)),
+            ),
+            (
+                "Caller (Big blind)".to_string(),
+                HandGrid::from_combos_weighted(&strategy[&vec![1]][1], &self.initial_reach(1)),
+            ),
+        ])
+    }
+}
+
+/// 各プレイヤーの名前 (ブラインドの位置)
+const BLIND_NAMES: [&str; 2] = ["Small blind", "Big blind"];
+
+/// 全ての履歴について、最初のアクション (フォールド・チェック) 以外のアクションの確率をグリッドにする
+fn action_grids<T: Game>(game: &T, strategy: &Strategy) -> Vec<(String, HandGrid)> {
+    let mut nodes = Vec::new();
+    collect_nodes(game.root(), &mut nodes);
+
+    let reach = [game.initial_reach(0), game.initial_reach(1)];
+    let mut ret = Vec::new();
+    for node in &nodes {
+        let history = node.public_history();
+        let reach = &reach[node.current_player()];
+        for action in node.actions().skip(1) {
+            ret.push((
+                format!(
+                    "{}: {} => {}",
+                    BLIND_NAMES[node.current_player()],
+                    history_label(game, history),
+                    node.action_name(action)
+                ),
+                HandGrid::from_combos_weighted(&strategy[history][action], reach),
+            ));
+        }
+    }
+    ret
+}
+
+/// 全ての履歴について、各アクションの全体の頻度と手札毎の確率を出力する
+///
+/// `details` はゲームのパラメータの説明で、`stacks` は期待値からハンド終了時のスタックの期待値を求めるために用いる
+fn tree_report<T: Game>(
+    game: &T,
+    strategy: &Strategy,
+    color: bool,
+    details: &str,
+    stacks: [f64; 2],
+) -> String {
+    let ev = compute_ev(game, 0, strategy);
+    let render = |grid: &HandGrid| match color {
+        true => grid.to_ansi(CellFormat::Percent),
+        false => grid.to_ascii(CellFormat::Percent),
+    };
+
+    let mut ret = String::new();
+    ret.push_str(&format!("\n[{}] ({})\n", game.name(), details));
+    ret.push_str(&format!(
+        "- Exploitability: {:+.3e}[bb]\n",
+        exploitability(game, strategy)
+    ));
+
+    let mut nodes = Vec::new();
+    collect_nodes(game.root(), &mut nodes);
+
+    for (player, player_ev) in [(0, ev), (1, -ev)] {
+        ret.push_str(&format!("\n[{}]\n", BLIND_NAMES[player]));
+        ret.push_str(&format!(
+            "- EV: {:+.4}[bb] (expected stack = {:.4}[bb])\n",
+            player_ev,
+            stacks[player] + player_ev
+        ));
+
+        // 初期レンジの外の手札は集計から除く
+        let reach = game.initial_reach(player);
+        for node in nodes.iter().filter(|n| n.current_player() == player) {
+            let history = node.public_history();
+            let probs = &strategy[history];
+            ret.push_str(&format!("\n[{}]\n", history_label(game, history)));
+            for action in node.actions() {
+                let grid = HandGrid::from_combos_weighted(&probs[action], &reach);
+                ret.push_str(&format!(
+                    "- {}: {:.2}%\n",
+                    node.action_name(action),
+                    100.0 * grid.overall()
+                ));
+            }
+            for action in node.actions().skip(1) {
+                ret.push_str(&format!("- {}%\n", node.action_name(action)));
+                ret.push_str(&render(&HandGrid::from_combos_weighted(
+                    &probs[action],
+                    &reach,
+                )));
+            }
+        }
+    }
+
+    ret
+}
+
+impl Report for LimpGame {
+    fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        LimpGame::from_parameters(parameters)
+    }
+
+    fn report(&self, strategy: &Strategy, color: bool) -> String {
+        let stacks = self.stacks();
+        let realization = self.realization();
+        let details = format!(
+            "stacks = {}/{}[bb], {}, realization = {}/{}",
+            stacks[0],
+            stacks[1],
+            self.blinds(),
+            realization[0],
+            realization[1]
+        );
+        tree_report(self, strategy, color, &details, stacks)
+    }
+
+    fn grids(&self, strategy: &Strategy) -> Option<Vec<(String, HandGrid)>> {
+        Some(action_grids(self, strategy))
+    }
+}
+
+impl Report for RaiseGame {
+    fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        RaiseGame::from_parameters(parameters)
+    }
+
+    fn report(&self, strategy: &Strategy, color: bool) -> String {
+        let stacks = self.stacks();
+        let realization = self.realization();
+        let raise_sizes = self
+            .raise_sizes()
+            .iter()
+            .map(|size| size.to_string())
+            .collect::<Vec<_>>();
+        let details = format!(
+            "stacks = {}/{}[bb], {}, raise sizes = {}[bb], realization = {}/{}",
+            stacks[0],
+            stacks[1],
+            self.blinds(),
+            raise_sizes.join("/"),
+            realization[0],
+            realization[1]
+        );
+        tree_report(self, strategy, color, &details, stacks)
+    }
+
+    fn grids(&self, strategy: &Strategy) -> Option<Vec<(String, HandGrid)>> {
+        Some(action_grids(self, strategy))
+    }
+}
+
+impl Report for TreeGame {
+    fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        TreeGame::from_parameters(parameters)
+    }
+
+    fn report(&self, strategy: &Strategy, color: bool) -> String {
+        let config = self.config();
+        let mut details = format!(
+            "stacks = {}/{}[bb], {}, open sizes = {}[bb], 3-bet sizes = {}x, 4-bet sizes = {}x, \
+             all-in threshold = {}, max raises = {}",
+            config.stacks[0],
+            config.stacks[1],
+            config.blinds,
+            format_sizes(&config.open_sizes, "/"),
+            format_sizes(&config.three_bet_sizes, "/"),
+            format_sizes(&config.four_bet_sizes, "/"),
+            config.allin_threshold,
+            config.max_raises
+        );
+        if config.allow_limp {
+            details.push_str(", limp allowed");
+        }
+        if !config.allow_allin {
+            details.push_str(", no all-in");
+        }
+        details.push_str(&format!(
+            ", realization = {}/{}",
+            config.realization[0], config.realization[1]
+        ));
+        tree_report(self, strategy, color, &details, config.stacks)
+    }
+
+    fn grids(&self, strategy: &Strategy) -> Option<Vec<(String, HandGrid)>> {
+        Some(action_grids(self, strategy))
+    }
+}
+
+impl Report for PloGame {
+    fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        PloGame::from_parameters(parameters)
+    }
+
+    fn report(&self, strategy: &Strategy, _color: bool) -> String {
+        // グリッドの代わりに、フォールド以外のアクションのレンジを手札の同値類単位で出力する
+        let stacks = self.stacks();
+        let ev = compute_ev(self, 0, strategy);
+
+        let mut ret = String::new();
+        ret.push_str(&format!(
+            "\n[{}] (stacks = {}/{}[bb], {})\n",
+            self.name(),
+            stacks[0],
+            stacks[1],
+            self.blinds()
+        ));
+        ret.push_str(&format!(
+            "- Exploitability: {:+.3e}[bb]\n",
+            exploitability(self, strategy)
+        ));
+
+        let mut nodes = Vec::new();
+        collect_nodes(self.root(), &mut nodes);
+
+        for (player, player_ev) in [(0, ev), (1, -ev)] {
+            ret.push_str(&format!("\n[{}]\n", BLIND_NAMES[player]));
+            ret.push_str(&format!(
+                "- EV: {:+.4}[bb] (expected stack = {:.4}[bb])\n",
+                player_ev,
+                stacks[player] + player_ev
+            ));
+
+            for node in nodes.iter().filter(|n| n.current_player() == player) {
+                let history = node.public_history();
+                let probs = &strategy[history];
+                ret.push_str(&format!("\n[{}]\n", history_label(self, history)));
+                for action in node.actions() {
+                    ret.push_str(&format!(
+                        "- {}: {:.2}%\n",
+                        node.action_name(action),
+                        100.0 * plo_overall(&probs[action])
+                    ));
+                }
+                for action in node.actions().skip(1) {
+                    ret.push_str(&format!(
+                        "- {} range: {}\n",
+                        node.action_name(action),
+                        format_plo_range(&probs[action])
+                    ));
+                }
+            }
+        }
+
+        ret
+    }
+}
@@ -0,0 +1,105 @@
+use super::*;
+use yabai_vol7_src::interface::*;
+use yabai_vol7_src::simulate::*;
+use yabai_vol7_src::strategy::*;
+use yabai_vol7_src::util::*;
+
+const USAGE: &str = "usage: yabai match <STRATEGY_A> <STRATEGY_B | br> [--deals <N>] \
+                     [--seed <N>] [--no-duplicate] [--table <PATH>] [--short-deck-table <PATH>] \
+                     [--tie-table <PATH>] [--short-deck-tie-table <PATH>] [--plo-table <PATH>]";
+
+/// 対戦シミュレーションを行って結果を出力する (`b` が `None` の場合は `a` の最適応答と対戦する)
+fn run_match<T: Game + Sync>(
+    game: &T,
+    a: &Strategy,
+    b: Option<&Strategy>,
+    config: &MatchConfig,
+) -> Result<(), String> {
+    let best_response;
+    let b = match b {
+        Some(b) => b,
+        None => {
+            best_response = compute_best_response_profile(game, a);
+            &best_response
+        }
+    };
+
+    let result = simulate_match(game, a, b, config)?;
+    let (lo, hi) = result.confidence_interval();
+
+    println!();
+    println!("[{}]", game.name());
+    println!("- A: {}", a.metadata.solver);
+    println!("- B: {}", b.metadata.solver);
+    println!(
+        "- Hands: {} ({}, seed = {})",
+        result.num_hands,
+        if config.duplicate {
+            "duplicate"
+        } else {
+            "single"
+        },
+        config.seed
+    );
+    println!(
+        "- Win rate of A: {:+.5} (± {:.5})",
+        result.mean, result.std_error
+    );
+    println!("- 95% confidence interval: [{:+.5}, {:+.5}]", lo, hi);
+    println!("- Exact EV of A: {:+.5}", compute_match_ev(game, a, b));
+    Ok(())
+}
+
+/// `yabai match`: 保存された2つの戦略を対戦させる
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &[
+            "deals",
+            "seed",
+            "table",
+            "short-deck-table",
+            "tie-table",
+            "short-deck-tie-table",
+            "plo-table",
+        ],
+        &["no-duplicate"],
+    )?;
+    if args.num_positional() != 2 {
+        return Err(USAGE.to_string());
+    }
+    apply_table_path(&args)?;
+
+    let default = MatchConfig::default();
+    let config = MatchConfig {
+        num_deals: args.get_or("deals", default.num_deals)?,
+        seed: args.get_or("seed", default.seed)?,
+        duplicate: !args.flag("no-duplicate"),
+    };
+    if config.num_deals == 0 {
+        return Err("'--deals' must be positive".to_string());
+    }
+
+    let a = load_strategy(args.positional(0, "STRATEGY_A")?)?;
+    if a.metadata.game_name == yabai_vol7_src::game_plo::GAME_NAME {
+        // ハンドクラスの組毎に配られる確率が異なるため、一様な棄却サンプリングでは配れない
+        return Err(format!(
+            "'match' does not support '{}'",
+            a.metadata.game_name
+        ));
+    }
+    let b = match args.positional(1, "STRATEGY_B")? {
+        "br" => None,
+        path => Some(load_strategy(path)?),
+    };
+    if let Some(b) = &b {
+        if a.metadata.game_name != b.metadata.game_name {
+            return Err(format!(
+                "cannot match strategies of different games: '{}' and '{}'",
+                a.metadata.game_name, b.metadata.game_name
+            ));
+        }
+    }
+
+    with_game!(a.metadata, |game| run_match(&game, &a, b.as_ref(), &config))
+}
@@ -0,0 +1,237 @@
+use super::export::render;
+use super::report::Report;
+use super::*;
+use yabai_vol7_src::cards::Deck;
+use yabai_vol7_src::cfr::*;
+use yabai_vol7_src::game_kuhn::KuhnGame;
+use yabai_vol7_src::game_limp::LimpGame;
+use yabai_vol7_src::game_plo::PloGame;
+use yabai_vol7_src::game_push_fold::*;
+use yabai_vol7_src::game_raise::*;
+use yabai_vol7_src::game_tree::*;
+use yabai_vol7_src::range::parse_range;
+use yabai_vol7_src::util::*;
+
+const USAGE: &str = "usage: yabai solve <kuhn | push-fold | plo | limp | raise | tree> [--cards <N>] \
+                     [--stack <X>] [--sb-stack <X>] [--bb-stack <X>] \
+                     [--raise-sizes <X,...>] [--sb-realization <X>] [--bb-realization <X>] \
+                     [--open-sizes <X,...>] [--3bet-sizes <X,...>] [--4bet-sizes <X,...>] \
+                     [--allin-threshold <X>] [--max-raises <N>] [--limp] [--no-allin] \
+                     [--sb <X>] [--bb <X>] [--ante <X>] [--bb-ante <X>] [--payouts <X,...>] [--other-stacks <X,...>] \
+                     [--sb-bounty <X>] [--bb-bounty <X>] [--bounty-immediate <X>] [--chips-per-bounty <X>] \
+                     [--rake <X>] [--rake-cap <X>] [--rake-preflop] [--deck standard|short] \
+                     [--sb-range <RANGE>] [--bb-range <RANGE>] \
+                     [--iterations <N>] [--solver <SOLVER>] [--table <PATH>] [--short-deck-table <PATH>] \
+                     [--tie-table <PATH>] [--short-deck-tie-table <PATH>] [--plo-table <PATH>] [--save <PATH>] \
+                     [--format text|json|csv|html|svg] [--output <PATH>] [--color]";
+
+/// ゲーム `game` を解き、結果を保存・出力する
+fn solve<T: Report>(game: &T, args: &Args, default_iterations: usize) -> Result<(), String> {
+    let num_iterations = args.get_or("iterations", default_iterations)?;
+    let variant = args.get_or("solver", SolverVariant::default())?;
+
+    let mut cfr = CFRMinimizer::with_variant(game, variant);
+    let mut strategy = cfr.compute(num_iterations);
+    strategy.metadata.exploitability = Some(compute_exploitability(game, &strategy));
+
+    if let Some(path) = args.get::<String>("save")? {
+        strategy
+            .save(&path)
+            .map_err(|e| format!("could not write '{}': {}", path, e))?;
+    }
+
+    let format = args.get_or("format", "text".to_string())?;
+    let contents = render(game, &strategy, &format, args.flag("color"))?;
+    write_output(args.get::<String>("output")?.as_deref(), &contents)
+}
+
+/// `--stack`, `--sb-stack`, `--bb-stack` とブラインドの指定から各プレイヤーのスタックを読み取る
+///
+/// `--sb-stack` / `--bb-stack` が指定されなかったプレイヤーのスタックは `--stack` とする
+fn parse_stacks(args: &Args) -> Result<([f64; 2], BlindStructure), String> {
+    let stack: f64 = args.get_or("stack", 10.0)?;
+    let stacks = [
+        args.get_or("sb-stack", stack)?,
+        args.get_or("bb-stack", stack)?,
+    ];
+    let blinds = parse_blinds(args)?;
+    check_stack(stacks[0].min(stacks[1]), &blinds)?;
+    Ok((stacks, blinds))
+}
+
+/// `--sb-range`, `--bb-range` から各プレイヤーの初期レンジを読み取り、`set` で設定する
+fn apply_initial_ranges(args: &Args, mut set: impl FnMut(usize, Vec<f64>)) -> Result<(), String> {
+    for (player, name) in ["sb-range", "bb-range"].into_iter().enumerate() {
+        if let Some(range) = args.get::<String>(name)? {
+            let range = parse_range(&range)
+                .map_err(|e| format!("invalid value for '--{}': {}", name, e))?;
+            set(player, range);
+        }
+    }
+    Ok(())
+}
+
+/// 初期レンジのもとで実現し得る配り方があることを確認してからゲーム `game` を解く
+fn solve_with_ranges<T: Report>(game: &T, args: &Args) -> Result<(), String> {
+    if joint_reach(game) <= 0.0 {
+        return Err("the initial ranges allow no valid deal".to_string());
+    }
+    solve(game, args, 1000)
+}
+
+/// `--sb-realization`, `--bb-realization` から各プレイヤーのエクイティ実現率を読み取る
+fn parse_realization(args: &Args) -> Result<[f64; 2], String> {
+    let realization = [
+        args.get_or("sb-realization", 1.0)?,
+        args.get_or("bb-realization", 1.0)?,
+    ];
+    match realization.iter().all(|&r| r > 0.0) {
+        true => Ok(realization),
+        false => Err("equity realization must be positive".to_string()),
+    }
+}
+
+/// `--open-sizes` などの指定からアクションツリーの設定を読み取る
+fn parse_tree_config(args: &Args) -> Result<TreeConfig, String> {
+    let (stacks, blinds) = parse_stacks(args)?;
+    let sizes = |name: &str, default: &str| -> Result<Vec<f64>, String> {
+        parse_sizes(&args.get_or(name, default.to_string())?)
+    };
+    let config = TreeConfig {
+        stacks,
+        blinds,
+        open_sizes: sizes("open-sizes", "2.5")?,
+        three_bet_sizes: sizes("3bet-sizes", "3")?,
+        four_bet_sizes: sizes("4bet-sizes", "2.2")?,
+        allin_threshold: args.get_or("allin-threshold", 1.0)?,
+        max_raises: args.get_or("max-raises", 4)?,
+        allow_limp: args.flag("limp"),
+        allow_allin: !args.flag("no-allin"),
+        realization: parse_realization(args)?,
+    };
+    config.validate()?;
+    Ok(config)
+}
+
+/// `yabai solve`: CFR でゲームを解く
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &[
+            "cards",
+            "stack",
+            "sb-stack",
+            "bb-stack",
+            "raise-sizes",
+            "open-sizes",
+            "3bet-sizes",
+            "4bet-sizes",
+            "allin-threshold",
+            "max-raises",
+            "sb-realization",
+            "bb-realization",
+            "sb",
+            "bb",
+            "ante",
+            "bb-ante",
+            "payouts",
+            "other-stacks",
+            "sb-bounty",
+            "bb-bounty",
+            "bounty-immediate",
+            "chips-per-bounty",
+            "rake",
+            "rake-cap",
+            "sb-range",
+            "bb-range",
+            "iterations",
+            "solver",
+            "table",
+            "short-deck-table",
+            "tie-table",
+            "short-deck-tie-table",
+            "plo-table",
+            "deck",
+            "save",
+            "format",
+            "output",
+        ],
+        &["color", "limp", "no-allin", "rake-preflop"],
+    )?;
+    if args.num_positional() != 1 {
+        return Err(USAGE.to_string());
+    }
+    apply_table_path(&args)?;
+
+    let game_name = args.positional(0, "GAME")?;
+    let has_range =
+        args.get::<String>("sb-range")?.is_some() || args.get::<String>("bb-range")?.is_some();
+    if has_range && !matches!(game_name, "push-fold" | "limp" | "raise" | "tree") {
+        return Err(format!("'{}' does not support initial ranges", game_name));
+    }
+
+    match game_name {
+        "kuhn" => {
+            let num_cards = args.get_or("cards", 3)?;
+            if num_cards < 2 {
+                return Err("'--cards' must be at least 2".to_string());
+            }
+            solve(&KuhnGame::with_num_cards(num_cards), &args, 10000)
+        }
+        "push-fold" => {
+            let (stacks, blinds) = parse_stacks(&args)?;
+            let mut game = PushFoldGame::with_stacks(stacks, blinds);
+            let icm = parse_icm(&args)?;
+            if let Some(bounty) = parse_bounty(&args, icm.is_some())? {
+                game.set_bounty(bounty);
+            }
+            if let Some(icm) = icm {
+                game.set_icm(icm);
+            }
+            if let Some(rake) = parse_rake(&args)? {
+                game.set_rake(rake);
+            }
+            game.set_deck(args.get_or("deck", Deck::Standard)?);
+            apply_initial_ranges(&args, |player, range| game.set_initial_range(player, range))?;
+            solve_with_ranges(&game, &args)
+        }
+        "plo" => {
+            let (stacks, blinds) = parse_stacks(&args)?;
+            solve(&PloGame::with_stacks(stacks, blinds), &args, 1000)
+        }
+        "limp" => {
+            let (stacks, blinds) = parse_stacks(&args)?;
+            let realization = parse_realization(&args)?;
+            let mut game = LimpGame::with_config(stacks, blinds, realization);
+            apply_initial_ranges(&args, |player, range| game.set_initial_range(player, range))?;
+            solve_with_ranges(&game, &args)
+        }
+        "raise" => {
+            let (stacks, blinds) = parse_stacks(&args)?;
+            let realization = parse_realization(&args)?;
+            let raise_sizes = match args.get::<String>("raise-sizes")? {
+                Some(s) => parse_raise_sizes(&s)?,
+                None => vec![2.0, 2.5],
+            };
+            let effective_stack = stacks[0].min(stacks[1]);
+            if raise_sizes
+                .iter()
+                .any(|&size| size <= blinds.big_blind || size + blinds.ante >= effective_stack)
+            {
+                return Err(
+                    "raise sizes must be greater than the big blind and less than the stack"
+                        .to_string(),
+                );
+            }
+            let mut game = RaiseGame::new(stacks, blinds, raise_sizes, realization);
+            apply_initial_ranges(&args, |player, range| game.set_initial_range(player, range))?;
+            solve_with_ranges(&game, &args)
+        }
+        "tree" => {
+            let mut game = TreeGame::new(parse_tree_config(&args)?);
+            apply_initial_ranges(&args, |player, range| game.set_initial_range(player, range))?;
+            solve_with_ranges(&game, &args)
+        }
+        game => Err(format!("unknown game: '{}'\n{}", game, USAGE)),
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use yabai_vol7_src::grid::*;
+use yabai_vol7_src::sweep::*;
+
+const USAGE: &str =
+    "usage: yabai sweep [--min <X>] [--max <X>] [--step <X>] [--sb <X>] [--bb <X>] \
+                     [--ante <X>] [--bb-ante <X>] [--iterations <N>] \
+                     [--solver <SOLVER>] [--table <PATH>] [--csv <PATH>] [--frequencies <PATH>] \
+                     [--svg <PATH>] [--color]";
+
+/// `write` によって `path` に書き出す
+fn save<F>(path: &str, write: F) -> Result<(), String>
+where
+    F: FnOnce(&mut BufWriter<File>) -> std::io::Result<()>,
+{
+    let result = File::create(path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        writer.flush()
+    });
+    result.map_err(|e| format!("could not write '{}': {}", path, e))
+}
+
+/// `yabai sweep`: 有効スタックを掃引してプッシュ/フォールドのチャートを作成する
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &[
+            "min",
+            "max",
+            "step",
+            "sb",
+            "bb",
+            "ante",
+            "bb-ante",
+            "iterations",
+            "solver",
+            "table",
+            "csv",
+            "frequencies",
+            "svg",
+        ],
+        &["color"],
+    )?;
+    if args.num_positional() != 0 {
+        return Err(USAGE.to_string());
+    }
+    apply_table_path(&args)?;
+
+    let default = SweepConfig::default();
+    let config = SweepConfig {
+        min_stack: args.get_or("min", default.min_stack)?,
+        max_stack: args.get_or("max", default.max_stack)?,
+        step: args.get_or("step", default.step)?,
+        blinds: parse_blinds(&args)?,
+        num_iterations: args.get_or("iterations", default.num_iterations)?,
+        variant: args.get_or("solver", default.variant)?,
+    };
+    if !(config.step > 0.0 && 0.0 < config.min_stack && config.min_stack <= config.max_stack) {
+        return Err("invalid stack range".to_string());
+    }
+    check_stack(config.min_stack, &config.blinds)?;
+
+    let sweep = sweep_push_fold(&config);
+    let push = sweep.thresholds(0);
+    let call = sweep.thresholds(1);
+    let render = |grid: &HandGrid| match args.flag("color") {
+        true => grid.to_ansi(CellFormat::Number),
+        false => grid.to_ascii(CellFormat::Number),
+    };
+    let max_exploitability = sweep
+        .exploitability
+        .iter()
+        .fold(0.0, |acc: f64, &x| acc.max(x));
+
+    println!();
+    println!(
+        "[Push/Fold Nash chart] (effective stack = {}..{}[bb], step = {}[bb], {})",
+        config.min_stack, config.max_stack, config.step, config.blinds
+    );
+    println!("- Stacks: {}", sweep.stacks.len());
+    println!("- Max exploitability: {:+.3e}[bb]", max_exploitability);
+    println!(
+        "- Each cell shows the largest stack [bb] at which the hand is played at least 50% \
+         (blank: never, {}: {} or more)",
+        config.max_stack, config.max_stack
+    );
+
+    println!();
+    println!("[Pusher (Small blind)]");
+    print!("{}", render(&push));
+
+    println!();
+    println!("[Caller (Big blind)]");
+    print!("{}", render(&call));
+
+    if let Some(path) = args.get::<String>("csv")? {
+        save(&path, |w| sweep.write_thresholds_csv(w))?;
+    }
+    if let Some(path) = args.get::<String>("frequencies")? {
+        save(&path, |w| sweep.write_frequencies_csv(w))?;
+    }
+    if let Some(path) = args.get::<String>("svg")? {
+        let grids = [
+            ("Pusher (Small blind)", &push),
+            ("Caller (Big blind)", &call),
+        ];
+        let svg = grids_to_svg(&grids, CellFormat::Number);
+        std::fs::write(&path, svg).map_err(|e| format!("could not write '{}': {}", path, e))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use crate::export::*;
+use crate::interface::*;
+use crate::strategy::*;
+use crate::util::*;
+
+/// ある履歴における2つの戦略の差分を表す構造体
+#[derive(Clone, Debug)]
+pub struct InfosetDiff {
+    /// パブリックな履歴
+    pub history: PublicHistory,
+
+    /// 履歴を表すアクション名の列
+    pub label: String,
+
+    /// 手番のプレイヤー
+    pub player: usize,
+
+    /// アクション名の一覧
+    pub actions: Vec<String>,
+
+    /// 比較元の戦略 (`[action][hand]`)
+    pub probs_a: Vec<Vec<f64>>,
+
+    /// 比較先の戦略 (`[action][hand]`)
+    pub probs_b: Vec<Vec<f64>>,
+
+    /// 比較元での各アクションの (手番のプレイヤーの到達確率で重み付けした) 全体の頻度
+    pub freq_a: Vec<f64>,
+
+    /// 比較先での各アクションの (手番のプレイヤーの到達確率で重み付けした) 全体の頻度
+    pub freq_b: Vec<f64>,
+}
+
+impl InfosetDiff {
+    /// 手札 `hand` において `action` を選ぶ確率の差分 (比較先 - 比較元) を返す
+    #[inline]
+    pub fn delta(&self, action: Action, hand: usize) -> f64 {
+        self.probs_b[action][hand] - self.probs_a[action][hand]
+    }
+
+    /// `action` を選ぶ確率の差分を手札毎に並べて返す
+    pub fn deltas(&self, action: Action) -> Vec<f64> {
+        (0..self.probs_a[action].len())
+            .map(|hand| self.delta(action, hand))
+            .collect()
+    }
+
+    /// 手札 `hand` におけるアクション間での差分の絶対値の最大値を返す
+    pub fn max_abs_delta(&self, hand: usize) -> f64 {
+        (0..self.actions.len())
+            .map(|action| self.delta(action, hand).abs())
+            .fold(0.0, f64::max)
+    }
+}
+
+/// 同じ形状のゲーム木における2つの戦略の差分を表す構造体
+#[derive(Clone, Debug)]
+pub struct StrategyDiff {
+    /// 手札の名前の一覧
+    pub hand_names: Vec<String>,
+
+    /// 履歴毎の差分 (深さ優先順)
+    pub infosets: Vec<InfosetDiff>,
+
+    /// 比較元での各プレイヤーの期待値
+    pub ev_a: [f64; 2],
+
+    /// 比較先での各プレイヤーの期待値
+    pub ev_b: [f64; 2],
+
+    /// 比較元での各プレイヤーの手札毎の期待値
+    pub hand_ev_a: [Vec<f64>; 2],
+
+    /// 比較先での各プレイヤーの手札毎の期待値
+    pub hand_ev_b: [Vec<f64>; 2],
+}
+
+/// 重み `weights` による `values` の加重平均を返す (重みの和が0の場合は0を返す)
+fn weighted_mean(values: &[f64], weights: &[f64]) -> f64 {
+    let weight_sum = weights.iter().sum::<f64>();
+    let sum = values.iter().zip(weights).map(|(v, w)| v * w).sum::<f64>();
+    match weight_sum > 0.0 {
+        true => sum / weight_sum,
+        false => 0.0,
+    }
+}
+
+/// 非終端ノードとそこでの手番のプレイヤーの戦略 `a`, `b` それぞれにおける到達確率を
+/// 深さ優先順に列挙する
+///
+/// `reach_a`, `reach_b` は `node` における各プレイヤーの到達確率 (初期レンジに自身のアクションの
+/// 確率を掛けたもの) である
+fn collect_reaches<N: GameNode>(
+    node: N,
+    reach_a: [Vec<f64>; 2],
+    reach_b: [Vec<f64>; 2],
+    a: &Strategy,
+    b: &Strategy,
+    nodes: &mut Vec<(N, Vec<f64>, Vec<f64>)>,
+) {
+    if node.is_terminal() {
+        return;
+    }
+
+    let player = node.current_player();
+    let history = node.public_history();
+    let probs_a = a.get(history).expect("missing history in strategy A");
+    let probs_b = b.get(history).expect("missing history in strategy B");
+    assert_eq!(probs_a.len(), probs_b.len(), "game trees differ");
+
+    let children = node
+        .actions()
+        .map(|action| {
+            let mut reach_a = reach_a.clone();
+            let mut reach_b = reach_b.clone();
+            reach_a[player] = multiply(&reach_a[player], &probs_a[action]);
+            reach_b[player] = multiply(&reach_b[player], &probs_b[action]);
+            (node.play(action), reach_a, reach_b)
+        })
+        .collect::<Vec<_>>();
+    nodes.push((node, reach_a[player].clone(), reach_b[player].clone()));
+    for (child, reach_a, reach_b) in children {
+        collect_reaches(child, reach_a, reach_b, a, b, nodes);
+    }
+}
+
+/// 要素毎の積を返す
+fn multiply(lhs: &[f64], rhs: &[f64]) -> Vec<f64> {
+    lhs.iter().zip(rhs).map(|(l, r)| l * r).collect()
+}
+
+/// ゲーム `game_a` における戦略 `a` と、ゲーム `game_b` における戦略 `b` の差分を計算する
+///
+/// 2つのゲームはパラメータ (スタックの大きさなど) が異なっていてもよいが、
+/// ゲーム木の形状と手札の個数は一致している必要がある
+pub fn diff_strategies<T: Game>(
+    game_a: &T,
+    a: &Strategy,
+    game_b: &T,
+    b: &Strategy,
+) -> StrategyDiff {
+    assert_eq!(game_a.num_private_hands(), game_b.num_private_hands());
+
+    let mut nodes = Vec::new();
+    collect_reaches(
+        game_a.root(),
+        [game_a.initial_reach(0), game_a.initial_reach(1)],
+        [game_b.initial_reach(0), game_b.initial_reach(1)],
+        a,
+        b,
+        &mut nodes,
+    );
+
+    let infosets = nodes
+        .iter()
+        .map(|(node, reach_a, reach_b)| {
+            let history = node.public_history();
+            let probs_a = &a[history];
+            let probs_b = &b[history];
+            let freq = |probs: &Vec<Vec<f64>>, reach: &[f64]| {
+                probs
+                    .iter()
+                    .map(|p| weighted_mean(p, reach))
+                    .collect::<Vec<_>>()
+            };
+
+            InfosetDiff {
+                history: history.clone(),
+                label: history_label(game_a, history),
+                player: node.current_player(),
+                actions: node.actions().map(|act| node.action_name(act)).collect(),
+                freq_a: freq(probs_a, reach_a),
+                freq_b: freq(probs_b, reach_b),
+                probs_a: probs_a.clone(),
+                probs_b: probs_b.clone(),
+            }
+        })
+        .collect();
+
+    StrategyDiff {
+        hand_names: (0..game_a.num_private_hands())
+            .map(|hand| game_a.hand_name(hand))
+            .collect(),
+        infosets,
+        ev_a: [compute_ev(game_a, 0, a), compute_ev(game_a, 1, a)],
+        ev_b: [compute_ev(game_b, 0, b), compute_ev(game_b, 1, b)],
+        hand_ev_a: [
+            compute_ev_per_hand(game_a, 0, a),
+            compute_ev_per_hand(game_a, 1, a),
+        ],
+        hand_ev_b: [
+            compute_ev_per_hand(game_b, 0, b),
+            compute_ev_per_hand(game_b, 1, b),
+        ],
+    }
+}
+
+impl StrategyDiff {
+    /// 各プレイヤーの手札毎の期待値の差分 (比較先 - 比較元) を返す
+    pub fn hand_ev_delta(&self, player: usize) -> Vec<f64> {
+        self.hand_ev_b[player]
+            .iter()
+            .zip(&self.hand_ev_a[player])
+            .map(|(b, a)| b - a)
+            .collect()
+    }
+
+    /// 期待値と各履歴における全体の頻度の変化を表形式の文字列にする
+    pub fn summary(&self) -> String {
+        let mut ret = String::new();
+        for player in 0..2 {
+            ret.push_str(&format!(
+                "- EV of player {}: {:+.4} => {:+.4} ({:+.4})\n",
+                player,
+                self.ev_a[player],
+                self.ev_b[player],
+                self.ev_b[player] - self.ev_a[player]
+            ));
+        }
+        for infoset in &self.infosets {
+            ret.push_str(&format!(
+                "- [{}] (player {})\n",
+                infoset.label, infoset.player
+            ));
+            for (action, name) in infoset.actions.iter().enumerate() {
+                let (a, b) = (infoset.freq_a[action], infoset.freq_b[action]);
+                ret.push_str(&format!(
+                    "    {}: {:.2}% => {:.2}% ({:+.2}%)\n",
+                    name,
+                    100.0 * a,
+                    100.0 * b,
+                    100.0 * (b - a)
+                ));
+            }
+        }
+        ret
+    }
+
+    /// 手札毎の確率の差分を表形式の文字列にする
+    ///
+    /// いずれかのアクションの確率の差分の絶対値が `min_delta` 以上の手札のみを出力する
+    pub fn to_table(&self, min_delta: f64) -> String {
+        let name_width = self.hand_names.iter().map(|s| s.len()).max().unwrap_or(0);
+        let mut ret = String::new();
+        for infoset in &self.infosets {
+            ret.push_str(&format!(
+                "[{}] (player {})\n",
+                infoset.label, infoset.player
+            ));
+            ret.push_str(&format!("{:>width$} |", "", width = name_width));
+            for name in &infoset.actions {
+                ret.push_str(&format!(" {:>24}", name));
+            }
+            ret.push('\n');
+
+            for (hand, hand_name) in self.hand_names.iter().enumerate() {
+                if infoset.max_abs_delta(hand) < min_delta {
+                    continue;
+                }
+                ret.push_str(&format!("{:>width$} |", hand_name, width = name_width));
+                for action in 0..infoset.actions.len() {
+                    let cell = format!(
+                        "{:.1}% => {:.1}% ({:+.1})",
+                        100.0 * infoset.probs_a[action][hand],
+                        100.0 * infoset.probs_b[action][hand],
+                        100.0 * infoset.delta(action, hand)
+                    );
+                    ret.push_str(&format!(" {:>24}", cell));
+                }
+                ret.push('\n');
+            }
+            ret.push('\n');
+        }
+        ret
+    }
+}
@@ -0,0 +1,279 @@
+use crate::cards::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// 圧縮形式の勝率テーブルの先頭に置かれる識別子
+pub const MAGIC: [u8; 4] = *b"YBEQ";
+
+/// 圧縮形式のバージョン
+pub const VERSION: u32 = 1;
+
+/// バイト列 `bytes` が圧縮形式の勝率テーブルかどうかを返す
+#[inline]
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+/// FNV-1a (64ビット) によるチェックサムを返す
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// 手札の番号の組 (1326 x 1326 通り) で添字付けられた勝率テーブル `table` を圧縮形式に変換する
+///
+/// スートの入れ替えで移り合う手札の組の値は等しいため、各手札をスートの入れ替えでハンドクラスの
+/// 代表に移し、代表を固定するスートの入れ替えで移り合う相手の手札をまとめて1つの値のみを格納する。
+/// 形式は以下の要素をリトルエンディアンで順に並べたものである:
+///
+/// - 識別子 `MAGIC` と バージョン `VERSION` (`u32`)
+/// - ハンドクラスの個数 `C` と格納する値の個数 `V` (`u32`)
+/// - 手札毎のハンドクラスの番号 (`u8` x 1326) と、代表に移すスートの入れ替え (`u8` x 4 x 1326)
+/// - ハンドクラス毎の値の開始位置 (`u32` x `C`)
+/// - ハンドクラス毎に、代表に移した相手の手札に対する値の位置 (開始位置からの差、`u16` x 1326 x `C`)
+/// - 値 (`i32` x `V`)
+/// - 以上のバイト列の FNV-1a によるチェックサム (`u64`)
+///
+/// 値がスートの入れ替えについて対称でない場合はエラーを返す
+pub fn compress(table: &[i32]) -> Result<Vec<u8>, String> {
+    if table.len() != NUM_COMBOS * NUM_COMBOS {
+        return Err(format!("invalid table length: {}", table.len()));
+    }
+    let permutations = suit_permutations();
+
+    // 各手札のハンドクラスの代表 (スートの入れ替えで移り合う手札のうち番号が最小のもの) と、
+    // 代表に移すスートの入れ替え
+    let mut reps = Vec::new();
+    let mut combo_class = vec![0; NUM_COMBOS];
+    let mut combo_perm = vec![[0; 4]; NUM_COMBOS];
+    for combo in 0..NUM_COMBOS {
+        let (rep, perm) = permutations
+            .iter()
+            .map(|perm| (permute_combo(combo, perm), *perm))
+            .min()
+            .unwrap();
+        combo_class[combo] = match reps.iter().position(|&r| r == rep) {
+            Some(class) => class,
+            None => {
+                reps.push(rep);
+                reps.len() - 1
+            }
+        };
+        combo_perm[combo] = perm;
+    }
+
+    // 代表を固定するスートの入れ替えで相手の手札を分類する
+    let mut offsets = Vec::with_capacity(reps.len());
+    let mut orbit_index = Vec::with_capacity(reps.len() * NUM_COMBOS);
+    let mut num_values = 0;
+    for &rep in &reps {
+        let stabilizer = permutations
+            .iter()
+            .filter(|perm| permute_combo(rep, perm) == rep)
+            .collect::<Vec<_>>();
+        let mut orbits = HashMap::new();
+        offsets.push(num_values);
+        for combo in 0..NUM_COMBOS {
+            let key = stabilizer
+                .iter()
+                .map(|perm| permute_combo(combo, perm))
+                .min()
+                .unwrap();
+            let num_orbits = orbits.len();
+            orbit_index.push(*orbits.entry(key).or_insert(num_orbits));
+        }
+        num_values += orbits.len();
+    }
+
+    // 値を格納し、同じ位置に格納される値が等しいことを確認する
+    let mut values = vec![None; num_values];
+    for a in 0..NUM_COMBOS {
+        let class = combo_class[a];
+        for b in 0..NUM_COMBOS {
+            let b_rep = permute_combo(b, &combo_perm[a]);
+            let index = offsets[class] + orbit_index[class * NUM_COMBOS + b_rep];
+            let value = table[a * NUM_COMBOS + b];
+            match values[index] {
+                None => values[index] = Some(value),
+                Some(v) if v == value => {}
+                Some(_) => return Err("table is not symmetric under suit permutations".to_string()),
+            }
+        }
+    }
+
+    let mut ret = Vec::new();
+    ret.extend(MAGIC);
+    ret.extend(VERSION.to_le_bytes());
+    ret.extend((reps.len() as u32).to_le_bytes());
+    ret.extend((num_values as u32).to_le_bytes());
+    ret.extend(combo_class.iter().map(|&class| class as u8));
+    ret.extend(
+        combo_perm
+            .iter()
+            .flat_map(|perm| perm.map(|suit| suit as u8)),
+    );
+    ret.extend(
+        offsets
+            .iter()
+            .flat_map(|&offset| (offset as u32).to_le_bytes()),
+    );
+    ret.extend(
+        orbit_index
+            .iter()
+            .flat_map(|&index| (index as u16).to_le_bytes()),
+    );
+    ret.extend(values.iter().flat_map(|value| value.unwrap().to_le_bytes()));
+    ret.extend(checksum(&ret).to_le_bytes());
+    Ok(ret)
+}
+
+/// 圧縮形式のバイト列 `bytes` を手札の番号の組で添字付けられた勝率テーブルに展開する
+pub fn decompress(bytes: &[u8]) -> Result<Vec<i32>, String> {
+    if !is_compressed(bytes) {
+        return Err("not a compressed equity table".to_string());
+    }
+    if bytes.len() < 24 {
+        return Err("truncated equity table".to_string());
+    }
+    let (body, sum) = bytes.split_at(bytes.len() - 8);
+    if checksum(body) != u64::from_le_bytes(sum.try_into().unwrap()) {
+        return Err("checksum mismatch".to_string());
+    }
+
+    let mut pos = MAGIC.len();
+    let mut take = |len: usize| -> Result<&[u8], String> {
+        let ret = body
+            .get(pos..pos + len)
+            .ok_or_else(|| "truncated equity table".to_string())?;
+        pos += len;
+        Ok(ret)
+    };
+    let read_u32 = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().unwrap());
+
+    let version = read_u32(take(4)?);
+    if version != VERSION {
+        return Err(format!("unsupported table version: {}", version));
+    }
+    let num_classes = read_u32(take(4)?) as usize;
+    let num_values = read_u32(take(4)?) as usize;
+    let combo_class = take(NUM_COMBOS)?.to_vec();
+    let combo_perm = take(4 * NUM_COMBOS)?.to_vec();
+    let offsets = take(4 * num_classes)?
+        .chunks(4)
+        .map(|chunk| read_u32(chunk) as usize)
+        .collect::<Vec<_>>();
+    let orbit_index = take(2 * num_classes * NUM_COMBOS)?;
+    let values = take(4 * num_values)?
+        .chunks(4)
+        .map(|chunk| i32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect::<Vec<_>>();
+
+    // ハンドクラスの代表の行 (代表に対する各手札の値) を展開する
+    let mut rep_rows = Vec::with_capacity(num_classes * NUM_COMBOS);
+    for (class, orbits) in orbit_index.chunks(2 * NUM_COMBOS).enumerate() {
+        for chunk in orbits.chunks(2) {
+            let index = offsets[class] + u16::from_le_bytes(chunk.try_into().unwrap()) as usize;
+            match values.get(index) {
+                Some(&value) => rep_rows.push(value),
+                None => return Err("invalid value index".to_string()),
+            }
+        }
+    }
+
+    // 現れるスートの入れ替え毎に、入れ替え後の手札の番号を前計算する
+    let cards = (0..NUM_COMBOS).map(combo_cards).collect::<Vec<_>>();
+    let mut permuted = HashMap::new();
+    for perm in combo_perm.chunks(4) {
+        if perm.iter().any(|&suit| suit >= 4) {
+            return Err("invalid suit permutation".to_string());
+        }
+        permuted.entry(perm).or_insert_with(|| {
+            cards
+                .iter()
+                .map(|&(i, j)| {
+                    let i = 4 * rank_of(i) + perm[suit_of(i)] as usize;
+                    let j = 4 * rank_of(j) + perm[suit_of(j)] as usize;
+                    combo_index(i.min(j), i.max(j))
+                })
+                .collect::<Vec<_>>()
+        });
+    }
+
+    if combo_class
+        .iter()
+        .any(|&class| class as usize >= num_classes)
+    {
+        return Err("invalid hand class".to_string());
+    }
+
+    // 各手札の行は、代表の行をスートの入れ替えに従って並べ替えたものである
+    let mut ret = vec![0; NUM_COMBOS * NUM_COMBOS];
+    ret.par_chunks_mut(NUM_COMBOS)
+        .enumerate()
+        .for_each(|(a, row)| {
+            let class = combo_class[a] as usize;
+            let rep_row = &rep_rows[class * NUM_COMBOS..(class + 1) * NUM_COMBOS];
+            let permuted = &permuted[&combo_perm[4 * a..4 * (a + 1)]];
+            row.iter_mut()
+                .zip(permuted)
+                .for_each(|(value, &b)| *value = rep_row[b]);
+        });
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// スートの入れ替えについて対称な値を持つテーブルを返す
+    ///
+    /// 値は各手札のランクと、4枚のカードのうち同じスートを持つ組の個数から定まる
+    fn symmetric_table() -> Vec<i32> {
+        let mut ret = vec![0; NUM_COMBOS * NUM_COMBOS];
+        for a in 0..NUM_COMBOS {
+            for b in 0..NUM_COMBOS {
+                let (i, j) = combo_cards(a);
+                let (m, n) = combo_cards(b);
+                let cards = [i, j, m, n];
+                let same_suit = (0..4)
+                    .flat_map(|x| ((x + 1)..4).map(move |y| (x, y)))
+                    .filter(|&(x, y)| suit_of(cards[x]) == suit_of(cards[y]))
+                    .count();
+                let ranks = (rank_of(j) * 13 + rank_of(i)) * 169 + rank_of(n) * 13 + rank_of(m);
+                ret[a * NUM_COMBOS + b] = (ranks * 7 + same_suit) as i32;
+            }
+        }
+        ret
+    }
+
+    #[test]
+    fn round_trip() {
+        let table = symmetric_table();
+        let bytes = compress(&table).unwrap();
+        assert!(is_compressed(&bytes));
+        assert!(bytes.len() < table.len());
+        assert_eq!(decompress(&bytes).unwrap(), table);
+    }
+
+    #[test]
+    fn rejects_asymmetric_table() {
+        let mut table = symmetric_table();
+        table[1] += 1;
+        assert!(compress(&table).is_err());
+    }
+
+    #[test]
+    fn rejects_corrupted_bytes() {
+        let bytes = compress(&symmetric_table()).unwrap();
+
+        let mut corrupted = bytes.clone();
+        let index = corrupted.len() / 2;
+        corrupted[index] ^= 1;
+        assert_eq!(decompress(&corrupted), Err("checksum mismatch".to_string()));
+
+        assert!(decompress(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decompress(&bytes[..16]).is_err());
+        assert!(decompress(b"not a table").is_err());
+    }
+}
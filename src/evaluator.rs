@@ -0,0 +1,234 @@
+use crate::cards::*;
+
+/// 役の種類 (通常のデッキでの弱い順)
+pub const HIGH_CARD: u32 = 0;
+pub const ONE_PAIR: u32 = 1;
+pub const TWO_PAIR: u32 = 2;
+pub const THREE_OF_A_KIND: u32 = 3;
+pub const STRAIGHT: u32 = 4;
+pub const FLUSH: u32 = 5;
+pub const FULL_HOUSE: u32 = 6;
+pub const FOUR_OF_A_KIND: u32 = 7;
+pub const STRAIGHT_FLUSH: u32 = 8;
+
+/// 役の種類 `category` のデッキ `deck` における強さの順位を返す
+///
+/// ショートデッキではフラッシュがフルハウスより強い
+#[inline]
+fn category_order(category: u32, deck: Deck) -> u32 {
+    match (deck, category) {
+        (Deck::ShortDeck, FLUSH) => FULL_HOUSE,
+        (Deck::ShortDeck, FULL_HOUSE) => FLUSH,
+        _ => category,
+    }
+}
+
+/// ランクの集合 `mask` の高いランクから `n` 個を、ビット位置 `shift` から4ビットずつ下位へ詰める
+#[inline]
+fn pack_top(mut mask: u16, n: usize, shift: u32) -> u32 {
+    let mut ret = 0;
+    for k in 0..n as u32 {
+        if mask == 0 {
+            break;
+        }
+        let rank = 15 - mask.leading_zeros();
+        ret |= rank << (shift - 4 * k);
+        mask &= !(1 << rank);
+    }
+    ret
+}
+
+/// ランクの集合 `mask` に含まれる最も強いストレートの最高位のランクを返す
+///
+/// エースはデッキの最も低いランクの下にも続く (通常のデッキでは A-2-3-4-5、
+/// ショートデッキでは A-6-7-8-9 がストレートとなる)
+#[inline]
+fn straight_high(mask: u16, deck: Deck) -> Option<u32> {
+    let lowest = deck.lowest_rank();
+    let runs = mask & (mask << 1) & (mask << 2) & (mask << 3) & (mask << 4);
+    if runs != 0 {
+        return Some(15 - runs.leading_zeros());
+    }
+    let wheel = (1 << 12) | (0b1111 << lowest);
+    match mask & wheel == wheel {
+        true => Some(lowest as u32 + 3),
+        false => None,
+    }
+}
+
+/// 最上位のランク (最も高いビット) を返す
+#[inline]
+fn highest(mask: u16) -> u32 {
+    15 - mask.leading_zeros()
+}
+
+/// カードの集合 `cards` (ビット `c` がカード `c` を表す、5枚以上) の最も強い役の強さを返す
+///
+/// 返り値が大きいほど強く、等しければ引き分けである
+pub fn evaluate_hand(cards: u64, deck: Deck) -> u32 {
+    let category = |category: u32| category_order(category, deck) << 20;
+
+    // スート毎のランクの集合と、2枚以上・3枚以上・4枚あるランクの集合
+    let mut suit_masks = [0u16; 4];
+    let mut rank_mask = 0u16;
+    let mut pair_mask = 0u16;
+    let mut trips_mask = 0u16;
+    let mut quads_mask = 0u16;
+    for rank in 0..13 {
+        let nibble = ((cards >> (4 * rank)) & 0xf) as u16;
+        for (suit, suit_mask) in suit_masks.iter_mut().enumerate() {
+            *suit_mask |= ((nibble >> suit) & 1) << rank;
+        }
+        let count = nibble.count_ones();
+        rank_mask |= ((count >= 1) as u16) << rank;
+        pair_mask |= ((count >= 2) as u16) << rank;
+        trips_mask |= ((count >= 3) as u16) << rank;
+        quads_mask |= ((count >= 4) as u16) << rank;
+    }
+
+    // ストレートフラッシュとフラッシュ
+    let mut flush = None;
+    for &suit_mask in &suit_masks {
+        if suit_mask.count_ones() >= 5 {
+            if let Some(high) = straight_high(suit_mask, deck) {
+                return category(STRAIGHT_FLUSH) | high << 16;
+            }
+            flush = Some(category(FLUSH) | pack_top(suit_mask, 5, 16));
+        }
+    }
+
+    // フォーカード
+    if quads_mask != 0 {
+        let quads = highest(quads_mask);
+        return category(FOUR_OF_A_KIND) | quads << 16 | pack_top(rank_mask & !(1 << quads), 1, 12);
+    }
+
+    // フルハウス (フラッシュとの強弱はデッキによる)
+    if trips_mask != 0 {
+        let three = highest(trips_mask);
+        let others = pair_mask & !(1 << three);
+        if others != 0 {
+            let full_house = category(FULL_HOUSE) | three << 16 | highest(others) << 12;
+            return flush.map_or(full_house, |flush| flush.max(full_house));
+        }
+    }
+    if let Some(flush) = flush {
+        return flush;
+    }
+
+    if let Some(high) = straight_high(rank_mask, deck) {
+        return category(STRAIGHT) | high << 16;
+    }
+
+    if trips_mask != 0 {
+        let three = highest(trips_mask);
+        return category(THREE_OF_A_KIND)
+            | three << 16
+            | pack_top(rank_mask & !(1 << three), 2, 12);
+    }
+
+    match pair_mask.count_ones() {
+        0 => category(HIGH_CARD) | pack_top(rank_mask, 5, 16),
+        1 => {
+            let pair = highest(pair_mask);
+            category(ONE_PAIR) | pair << 16 | pack_top(rank_mask & !(1 << pair), 3, 12)
+        }
+        _ => {
+            let first = highest(pair_mask);
+            let second = highest(pair_mask & !(1 << first));
+            let kicker = pack_top(rank_mask & !(1 << first) & !(1 << second), 1, 8);
+            category(TWO_PAIR) | first << 16 | second << 12 | kicker
+        }
+    }
+}
+
+/// オマハにおいて、手札 `hole` (4枚) とボード `board` (5枚) から手札をちょうど2枚、ボードを
+/// ちょうど3枚用いて作れる最も強い役の強さを返す (カードの集合の表現は `evaluate_hand()` と同じ)
+pub fn evaluate_omaha(hole: u64, board: u64, deck: Deck) -> u32 {
+    // カードの集合を1枚ずつのカードの列に分解する
+    let split = |mut mask: u64| {
+        let mut ret = [0u64; 5];
+        let mut len = 0;
+        while mask != 0 && len < 5 {
+            ret[len] = 1 << mask.trailing_zeros();
+            mask &= mask - 1;
+            len += 1;
+        }
+        (ret, len)
+    };
+    let (hole, num_hole) = split(hole);
+    let (board, num_board) = split(board);
+    let (hole, board) = (&hole[..num_hole], &board[..num_board]);
+
+    let mut ret = 0;
+    for i in 0..hole.len() {
+        for j in (i + 1)..hole.len() {
+            let two = hole[i] | hole[j];
+            for k in 0..board.len() {
+                for l in (k + 1)..board.len() {
+                    for &m in &board[(l + 1)..] {
+                        ret = ret.max(evaluate_hand(two | board[k] | board[l] | m, deck));
+                    }
+                }
+            }
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// "AsKs..." のような表記のカードの集合を返す
+    fn cards(s: &str) -> u64 {
+        let chars = s.chars().collect::<Vec<_>>();
+        chars.chunks(2).fold(0, |acc, card| {
+            let card = parse_card(&card.iter().collect::<String>()).unwrap();
+            acc | 1 << card
+        })
+    }
+
+    #[test]
+    fn short_deck_flush_beats_full_house() {
+        let flush = cards("AhJh9h7h6hKsQd");
+        let full_house = cards("KsKhKdQsQhAc6c");
+        assert!(evaluate_hand(flush, Deck::ShortDeck) > evaluate_hand(full_house, Deck::ShortDeck));
+        assert!(evaluate_hand(flush, Deck::Standard) < evaluate_hand(full_house, Deck::Standard));
+    }
+
+    #[test]
+    fn short_deck_wheel_is_straight() {
+        let wheel = cards("As6d7c8h9sJdQc");
+        let trips = cards("QsQdQhKc9s8d6c");
+        assert!(evaluate_hand(wheel, Deck::ShortDeck) > evaluate_hand(trips, Deck::ShortDeck));
+
+        // A-6-7-8-9 は 6-7-8-9-T より弱い
+        let six_high = cards("6d7c8h9sTdQcKh");
+        assert!(evaluate_hand(wheel, Deck::ShortDeck) < evaluate_hand(six_high, Deck::ShortDeck));
+
+        // 通常のデッキではストレートではない
+        let ace_high = cards("As6d7c8h9sJdQc");
+        let pair = cards("2s2d7c8h9sJdQc");
+        assert!(evaluate_hand(ace_high, Deck::Standard) < evaluate_hand(pair, Deck::Standard));
+    }
+
+    #[test]
+    fn standard_wheel_is_straight() {
+        let wheel = cards("As2d3c4h5sJdQc");
+        let trips = cards("QsQdQhKc9s8d6c");
+        assert!(evaluate_hand(wheel, Deck::Standard) > evaluate_hand(trips, Deck::Standard));
+    }
+
+    #[test]
+    fn omaha_uses_exactly_two_hole_cards() {
+        // ボードの4枚のスペードと手札の1枚のスペードではフラッシュにならない
+        let hole = cards("As2d3c4h");
+        let board = cards("KsQsJs9s5d");
+        let flush = cards("As2sKd3c");
+        assert!(
+            evaluate_omaha(hole, board, Deck::Standard)
+                < evaluate_omaha(flush, board, Deck::Standard)
+        );
+    }
+}
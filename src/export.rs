@@ -0,0 +1,232 @@
+use crate::interface::*;
+use crate::strategy::*;
+use crate::util::*;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// 非終端ノードを深さ優先順に列挙する
+pub fn collect_nodes<N: GameNode>(node: N, nodes: &mut Vec<N>) {
+    if node.is_terminal() {
+        return;
+    }
+    let children = node
+        .actions()
+        .map(|action| node.play(action))
+        .collect::<Vec<_>>();
+    nodes.push(node);
+    for child in children {
+        collect_nodes(child, nodes);
+    }
+}
+
+/// 履歴 `history` を "Check => Bet" のようなアクション名の列に変換する (根は "root")
+pub fn history_label<T: Game>(game: &T, history: &[Action]) -> String {
+    if history.is_empty() {
+        return "root".to_string();
+    }
+
+    let mut node = game.root();
+    let mut names = Vec::with_capacity(history.len());
+    for &action in history {
+        names.push(node.action_name(action));
+        node = node.play(action);
+    }
+    names.join(" => ")
+}
+
+/// JSON の文字列リテラルに変換する
+fn json_string(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len() + 2);
+    ret.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            '\t' => ret.push_str("\\t"),
+            c if (c as u32) < 0x20 => ret.push_str(&format!("\\u{:04x}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+/// JSON の数値に変換する (有限でない値は `null`)
+fn json_number(x: f64) -> String {
+    if x.is_finite() {
+        format!("{}", x)
+    } else {
+        "null".to_string()
+    }
+}
+
+/// CSV のフィールドに変換する
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// `player` に配られ得る (初期レンジの値が正の) 手札の一覧を返す
+fn dealt_hands<T: Game>(game: &T, player: usize) -> Vec<usize> {
+    let reach = game.initial_reach(player);
+    (0..game.num_private_hands())
+        .filter(|&hand| reach[hand] > 0.0)
+        .collect()
+}
+
+/// 戦略の組 `strategy` と各プレイヤーの期待値を JSON 形式で書き出す
+///
+/// 各プレイヤーの手札毎の値は、そのプレイヤーに配られ得る (初期レンジの値が正の) 手札についてのみ書き出す
+pub fn write_json<T: Game, W: Write>(
+    writer: &mut W,
+    game: &T,
+    strategy: &Strategy,
+) -> io::Result<()> {
+    let meta = &strategy.metadata;
+    let num_hands = game.num_private_hands();
+    let hand_names = (0..num_hands)
+        .map(|hand| json_string(&game.hand_name(hand)))
+        .collect::<Vec<_>>();
+
+    writeln!(writer, "{{")?;
+    writeln!(writer, "  \"game\": {},", json_string(&meta.game_name))?;
+    let parameters = meta
+        .parameters
+        .iter()
+        .map(|(k, v)| format!("{}: {}", json_string(k), json_string(v)))
+        .collect::<Vec<_>>();
+    writeln!(writer, "  \"parameters\": {{{}}},", parameters.join(", "))?;
+    writeln!(writer, "  \"num_iterations\": {},", meta.num_iterations)?;
+    let exploitability = meta.exploitability.map_or("null".to_string(), json_number);
+    writeln!(writer, "  \"exploitability\": {},", exploitability)?;
+    writeln!(writer, "  \"solver\": {},", json_string(&meta.solver))?;
+
+    // 各プレイヤーの期待値
+    writeln!(writer, "  \"players\": [")?;
+    let dealt = [dealt_hands(game, 0), dealt_hands(game, 1)];
+    for (player, hands) in dealt.iter().enumerate() {
+        let ev = compute_ev(game, player, strategy);
+        let ev_per_hand = compute_ev_per_hand(game, player, strategy);
+        let hand_ev = hands
+            .iter()
+            .map(|&hand| format!("{}: {}", hand_names[hand], json_number(ev_per_hand[hand])))
+            .collect::<Vec<_>>();
+        writeln!(writer, "    {{")?;
+        writeln!(writer, "      \"player\": {},", player)?;
+        writeln!(writer, "      \"ev\": {},", json_number(ev))?;
+        writeln!(writer, "      \"hand_ev\": {{{}}}", hand_ev.join(", "))?;
+        writeln!(writer, "    }}{}", if player == 0 { "," } else { "" })?;
+    }
+    writeln!(writer, "  ],")?;
+
+    // 各履歴における戦略
+    let mut nodes = Vec::new();
+    collect_nodes(game.root(), &mut nodes);
+    writeln!(writer, "  \"infosets\": [")?;
+    for (index, node) in nodes.iter().enumerate() {
+        let history = node.public_history();
+        let names = node
+            .actions()
+            .map(|action| node.action_name(action))
+            .collect::<Vec<_>>();
+        let probs = &strategy[history];
+        let hand_strategy = dealt[node.current_player()]
+            .iter()
+            .map(|&hand| {
+                let action_probs = names
+                    .iter()
+                    .zip(probs)
+                    .map(|(name, p)| format!("{}: {}", json_string(name), json_number(p[hand])))
+                    .collect::<Vec<_>>();
+                format!("{}: {{{}}}", hand_names[hand], action_probs.join(", "))
+            })
+            .collect::<Vec<_>>();
+        let path = history.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        let names = names
+            .iter()
+            .map(|name| json_string(name))
+            .collect::<Vec<_>>();
+
+        writeln!(writer, "    {{")?;
+        writeln!(writer, "      \"history\": [{}],", path.join(", "))?;
+        let label = history_label(game, history);
+        writeln!(writer, "      \"label\": {},", json_string(&label))?;
+        writeln!(writer, "      \"player\": {},", node.current_player())?;
+        writeln!(writer, "      \"actions\": [{}],", names.join(", "))?;
+        writeln!(
+            writer,
+            "      \"strategy\": {{{}}}",
+            hand_strategy.join(", ")
+        )?;
+        let separator = if index + 1 < nodes.len() { "," } else { "" };
+        writeln!(writer, "    }}{}", separator)?;
+    }
+    writeln!(writer, "  ]")?;
+    writeln!(writer, "}}")?;
+
+    Ok(())
+}
+
+/// 戦略の組 `strategy` を CSV 形式で書き出す
+///
+/// 各行は `history,player,hand,action,probability,ev` の形式で、`ev` は手番のプレイヤーが
+/// その手札を持つときの (ゲーム全体での) 利得の期待値である。
+/// 手番のプレイヤーに配られ得ない (初期レンジの値が0の) 手札の行は書き出さない
+pub fn write_csv<T: Game, W: Write>(
+    writer: &mut W,
+    game: &T,
+    strategy: &Strategy,
+) -> io::Result<()> {
+    let dealt = [dealt_hands(game, 0), dealt_hands(game, 1)];
+    let ev_per_hand = [
+        compute_ev_per_hand(game, 0, strategy),
+        compute_ev_per_hand(game, 1, strategy),
+    ];
+
+    let mut nodes = Vec::new();
+    collect_nodes(game.root(), &mut nodes);
+
+    writeln!(writer, "history,player,hand,action,probability,ev")?;
+    for node in &nodes {
+        let history = node.public_history();
+        let label = csv_field(&history_label(game, history));
+        let player = node.current_player();
+        let probs = &strategy[history];
+        for &hand in &dealt[player] {
+            let hand_name = csv_field(&game.hand_name(hand));
+            for action in node.actions() {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{},{}",
+                    label,
+                    player,
+                    hand_name,
+                    csv_field(&node.action_name(action)),
+                    probs[action][hand],
+                    ev_per_hand[player][hand]
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 戦略の組 `strategy` を `path` に JSON 形式で保存する
+pub fn save_json<T: Game>(path: &str, game: &T, strategy: &Strategy) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_json(&mut writer, game, strategy)?;
+    writer.flush()
+}
+
+/// 戦略の組 `strategy` を `path` に CSV 形式で保存する
+pub fn save_csv<T: Game>(path: &str, game: &T, strategy: &Strategy) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_csv(&mut writer, game, strategy)?;
+    writer.flush()
+}
@@ -1,9 +1,15 @@
 use crate::interface::*;
+use crate::strategy::parse_parameter;
 
 const CHECK_FOLD: usize = 0;
 const BET_CALL: usize = 1;
 
-pub struct KuhnGame {}
+/// ゲームの名前
+pub const GAME_NAME: &str = "Kuhn Poker";
+
+pub struct KuhnGame {
+    num_cards: usize,
+}
 
 #[derive(Clone)]
 pub struct KuhnNode {
@@ -14,39 +20,90 @@ impl Game for KuhnGame {
     type Node = KuhnNode;
 
     #[inline]
-    fn root() -> KuhnNode {
+    fn name(&self) -> String {
+        GAME_NAME.to_string()
+    }
+
+    #[inline]
+    fn parameters(&self) -> Vec<(String, String)> {
+        vec![("num_cards".to_string(), self.num_cards.to_string())]
+    }
+
+    #[inline]
+    fn root(&self) -> KuhnNode {
         KuhnNode {
             public_history: Vec::new(),
         }
     }
 
     #[inline]
-    fn num_private_hands() -> usize {
-        3
+    fn num_private_hands(&self) -> usize {
+        self.num_cards
+    }
+
+    #[inline]
+    fn hand_name(&self, hand: usize) -> String {
+        match self.num_cards {
+            3 => ["J", "Q", "K"][hand].to_string(),
+            _ => hand.to_string(),
+        }
+    }
+
+    #[inline]
+    fn chance_reach(&self, pmi: &[f64]) -> Vec<f64> {
+        let num_deals = (self.num_cards * (self.num_cards - 1)) as f64;
+        let pmi_sum = pmi.iter().sum::<f64>();
+        pmi.iter().map(|p| (pmi_sum - p) / num_deals).collect()
     }
 
     #[inline]
     fn evaluate(&self, node: &KuhnNode, player: usize, pmi: &Vec<f64>) -> Vec<f64> {
-        let mut cfvalue = vec![0.0; Self::num_private_hands()];
+        let num_deals = (self.num_cards * (self.num_cards - 1)) as f64;
+        let mut cfvalue = vec![0.0; self.num_private_hands()];
 
-        for my_card in 0..Self::num_private_hands() {
-            for opp_card in 0..Self::num_private_hands() {
+        for (my_card, cfvalue) in cfvalue.iter_mut().enumerate() {
+            for (opp_card, &pmi) in pmi.iter().enumerate() {
                 if my_card == opp_card {
                     continue;
                 }
-                cfvalue[my_card] +=
-                    Self::payoff(node, player, my_card, opp_card) * pmi[opp_card] / 6.0;
+                *cfvalue += Self::payoff(node, player, my_card, opp_card) * pmi / num_deals;
             }
         }
 
         cfvalue
     }
+
+    #[inline]
+    fn is_valid_deal(&self, hands: [usize; 2]) -> bool {
+        hands[0] != hands[1]
+    }
+
+    #[inline]
+    fn evaluate_hands(&self, node: &KuhnNode, player: usize, hands: [usize; 2]) -> f64 {
+        Self::payoff(node, player, hands[player], hands[player ^ 1])
+    }
 }
 
 impl KuhnGame {
+    /// 3枚のカード (J, Q, K) を用いる標準的な Kuhn poker を返す
     #[inline]
     pub fn new() -> Self {
-        Self {}
+        Self::with_num_cards(3)
+    }
+
+    /// `num_cards` 枚のカードを用いる Kuhn poker の変種を返す
+    #[inline]
+    pub fn with_num_cards(num_cards: usize) -> Self {
+        assert!(num_cards >= 2, "Kuhn poker requires at least 2 cards");
+        Self { num_cards }
+    }
+
+    /// `Game::parameters()` が返すパラメータの列からゲームを復元する
+    pub fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        Ok(Self::with_num_cards(parse_parameter(
+            parameters,
+            "num_cards",
+        )?))
     }
 
     #[inline]
@@ -62,6 +119,13 @@ impl KuhnGame {
     }
 }
 
+impl Default for KuhnGame {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GameNode for KuhnNode {
     #[inline]
     fn public_history(&self) -> &PublicHistory {
@@ -88,6 +152,17 @@ impl GameNode for KuhnNode {
         2
     }
 
+    #[inline]
+    fn action_name(&self, action: Action) -> String {
+        match (self.public_history.last(), action) {
+            (Some(&BET_CALL), CHECK_FOLD) => "Fold",
+            (Some(&BET_CALL), _) => "Call",
+            (_, CHECK_FOLD) => "Check",
+            _ => "Bet",
+        }
+        .to_string()
+    }
+
     #[inline]
     fn play(&self, action: Action) -> Self {
         let mut ret = self.clone();
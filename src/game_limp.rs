@@ -0,0 +1,325 @@
+use crate::cards::*;
+use crate::game_push_fold::*;
+use crate::interface::*;
+use crate::range::InitialRanges;
+use crate::strategy::{parse_parameter, parse_parameter_or};
+
+// スモールブラインドの最初の手番のアクション
+const FOLD: usize = 0;
+const LIMP: usize = 1;
+const SHOVE: usize = 2;
+
+// それ以降の手番のアクション
+const CHECK_FOLD: usize = 0;
+const SHOVE_CALL: usize = 1;
+
+/// ゲームの名前
+pub const GAME_NAME: &str = "Heads-up Limp/Push/Fold Hold'em";
+
+/// スモールブラインドがフォールド・リンプ・オールインを選べるヘッズアップのプリフロップ
+///
+/// リンプに対してビッグブラインドがチェックした場合はポストフローに進む代わりに、
+/// 勝率に各プレイヤーのエクイティ実現率を掛けた値の比でポットを分け合うものとみなす
+pub struct LimpGame {
+    stacks: [f64; 2],
+    blinds: BlindStructure,
+    realization: [f64; 2],
+    ranges: InitialRanges,
+}
+
+#[derive(Clone)]
+pub struct LimpNode {
+    public_history: PublicHistory,
+}
+
+/// 終端ノードの種類
+enum Terminal {
+    /// `player` がフォールドした
+    Fold(usize),
+
+    /// オールインのショーダウン
+    AllIn,
+
+    /// リンプに対してチェックし、ポストフロップに進んだ
+    Limped,
+}
+
+impl Game for LimpGame {
+    type Node = LimpNode;
+
+    #[inline]
+    fn name(&self) -> String {
+        GAME_NAME.to_string()
+    }
+
+    #[inline]
+    fn parameters(&self) -> Vec<(String, String)> {
+        let mut ret = vec![
+            ("sb_stack".to_string(), self.stacks[0].to_string()),
+            ("bb_stack".to_string(), self.stacks[1].to_string()),
+        ];
+        ret.extend(self.blinds.parameters());
+        ret.extend([
+            (
+                "sb_realization".to_string(),
+                self.realization[0].to_string(),
+            ),
+            (
+                "bb_realization".to_string(),
+                self.realization[1].to_string(),
+            ),
+        ]);
+        ret.extend(self.ranges.parameters());
+        ret
+    }
+
+    #[inline]
+    fn root(&self) -> LimpNode {
+        LimpNode {
+            public_history: Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn num_private_hands(&self) -> usize {
+        NUM_COMBOS
+    }
+
+    #[inline]
+    fn initial_reach(&self, player: usize) -> Vec<f64> {
+        self.ranges.get(player).to_vec()
+    }
+
+    #[inline]
+    fn hand_name(&self, hand: usize) -> String {
+        combo_to_string(hand)
+    }
+
+    #[inline]
+    fn chance_reach(&self, pmi: &[f64]) -> Vec<f64> {
+        holdem_chance_reach(Deck::Standard, pmi)
+    }
+
+    #[inline]
+    fn evaluate(&self, node: &LimpNode, player: usize, pmi: &Vec<f64>) -> Vec<f64> {
+        match node.terminal() {
+            Terminal::Fold(folder) => {
+                let payoff = self.fold_payoff(node, folder, player);
+                let mut ret = self.chance_reach(pmi);
+                ret.iter_mut().for_each(|el| *el *= payoff);
+                ret
+            }
+            Terminal::AllIn => showdown_cfvalues(Deck::Standard, pmi, |equity| {
+                self.blinds.allin_payoff(self.stacks, player, equity)
+            }),
+            Terminal::Limped => showdown_cfvalues(Deck::Standard, pmi, |equity| {
+                self.limped_payoff(player, equity)
+            }),
+        }
+    }
+
+    #[inline]
+    fn is_valid_deal(&self, hands: [usize; 2]) -> bool {
+        combos_disjoint(hands[0], hands[1])
+    }
+
+    #[inline]
+    fn evaluate_hands(&self, node: &LimpNode, player: usize, hands: [usize; 2]) -> f64 {
+        let equity = || equity(Deck::Standard, [hands[player], hands[player ^ 1]]);
+        match node.terminal() {
+            Terminal::Fold(folder) => self.fold_payoff(node, folder, player),
+            Terminal::AllIn => self.blinds.allin_payoff(self.stacks, player, equity()),
+            Terminal::Limped => self.limped_payoff(player, equity()),
+        }
+    }
+}
+
+impl LimpGame {
+    /// 有効スタック `effective_stack` と既定のブラインドで、勝率をそのまま実現するゲームを作る
+    pub fn new(effective_stack: f64) -> Self {
+        Self::with_config(
+            [effective_stack, effective_stack],
+            BlindStructure::default(),
+            [1.0, 1.0],
+        )
+    }
+
+    /// 各プレイヤーのスタック `stacks`、ブラインドとアンティの構造 `blinds`、
+    /// リンプ・チェック後のエクイティ実現率 `realization` を指定するコンストラクタ
+    ///
+    /// スタックはブラインドとアンティを支払う前のもので、
+    /// オールインのショーダウンでは両者がアンティを除いた有効スタックの額を賭ける
+    pub fn with_config(stacks: [f64; 2], blinds: BlindStructure, realization: [f64; 2]) -> Self {
+        assert!(0.0 <= blinds.small_blind && blinds.small_blind <= blinds.big_blind);
+        assert!(blinds.ante >= 0.0 && blinds.bb_ante >= 0.0);
+        assert!(
+            blinds.posted(1) <= stacks[0].min(stacks[1]),
+            "effective stack must cover the blinds and antes"
+        );
+        assert!(realization.iter().all(|&r| r > 0.0));
+        Self {
+            stacks,
+            blinds,
+            realization,
+            ranges: InitialRanges::default(),
+        }
+    }
+
+    /// `Game::parameters()` が返すパラメータの列からゲームを復元する
+    pub fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        let blinds = BlindStructure::from_parameters(parameters)?;
+        let stacks = [
+            parse_parameter(parameters, "sb_stack")?,
+            parse_parameter(parameters, "bb_stack")?,
+        ];
+        let realization = [
+            parse_parameter_or(parameters, "sb_realization", 1.0)?,
+            parse_parameter_or(parameters, "bb_realization", 1.0)?,
+        ];
+        let mut ret = Self::with_config(stacks, blinds, realization);
+        ret.ranges = InitialRanges::from_parameters(parameters)?;
+        Ok(ret)
+    }
+
+    /// 各プレイヤーのスタック (単位: bb) を返す
+    #[inline]
+    pub fn stacks(&self) -> [f64; 2] {
+        self.stacks
+    }
+
+    /// 有効スタック (単位: bb) を返す
+    #[inline]
+    pub fn effective_stack(&self) -> f64 {
+        self.stacks[0].min(self.stacks[1])
+    }
+
+    /// ブラインドとアンティの構造を返す
+    #[inline]
+    pub fn blinds(&self) -> &BlindStructure {
+        &self.blinds
+    }
+
+    /// リンプ・チェック後の各プレイヤーのエクイティ実現率を返す
+    #[inline]
+    pub fn realization(&self) -> [f64; 2] {
+        self.realization
+    }
+
+    /// `player` の初期レンジを設定する (`range` の各要素は手札が実現している確率)
+    pub fn set_initial_range(&mut self, player: usize, range: Vec<f64>) {
+        self.ranges.set(player, range);
+    }
+
+    /// 利得の期待値 `ev` から `player` のハンド終了時のスタックの期待値を返す
+    #[inline]
+    pub fn expected_stack(&self, player: usize, ev: f64) -> f64 {
+        self.stacks[player] + ev
+    }
+
+    /// `player` がリンプ・チェックの時点までにポットに入れた額を返す
+    #[inline]
+    fn limped_contribution(&self, player: usize) -> f64 {
+        match player {
+            0 => self.blinds.big_blind + self.blinds.ante,
+            _ => self.blinds.posted(1),
+        }
+    }
+
+    /// `folder` がフォールドした終端ノード `node` における `player` の利得を返す
+    #[inline]
+    fn fold_payoff(&self, node: &LimpNode, folder: usize, player: usize) -> f64 {
+        // フォールドしたプレイヤーはそれまでにポットに入れた額を失う
+        let payoff = match node.public_history.first() {
+            Some(&LIMP) => self.limped_contribution(folder),
+            _ => self.blinds.posted(folder),
+        };
+        match player == folder {
+            true => -payoff,
+            false => payoff,
+        }
+    }
+
+    /// リンプ・チェックの後、`player` の勝率が `equity` のときの利得を返す
+    #[inline]
+    fn limped_payoff(&self, player: usize, equity: f64) -> f64 {
+        let pot = self.limped_contribution(0) + self.limped_contribution(1);
+        let share = realized_share(self.realization, player, equity);
+        share * pot - self.limped_contribution(player)
+    }
+}
+
+/// エクイティ実現率 `realization` のもとで、勝率 `equity` の `player` が得るポットの割合を返す
+///
+/// ポットは勝率とエクイティ実現率の積の比で分け合う
+#[inline]
+pub(crate) fn realized_share(realization: [f64; 2], player: usize, equity: f64) -> f64 {
+    let mine = realization[player] * equity;
+    let theirs = realization[player ^ 1] * (1.0 - equity);
+    match mine + theirs {
+        total if total > 0.0 => mine / total,
+        _ => 0.5,
+    }
+}
+
+impl LimpNode {
+    /// 終端ノードの種類を返す
+    #[inline]
+    fn terminal(&self) -> Terminal {
+        match self.public_history.as_slice() {
+            [FOLD] => Terminal::Fold(0),
+            [SHOVE, CHECK_FOLD] => Terminal::Fold(1),
+            [LIMP, SHOVE_CALL, CHECK_FOLD] => Terminal::Fold(0),
+            [LIMP, CHECK_FOLD] => Terminal::Limped,
+            _ => Terminal::AllIn,
+        }
+    }
+}
+
+impl GameNode for LimpNode {
+    #[inline]
+    fn public_history(&self) -> &PublicHistory {
+        &self.public_history
+    }
+
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        !matches!(
+            self.public_history.as_slice(),
+            [] | [LIMP] | [SHOVE] | [LIMP, SHOVE_CALL]
+        )
+    }
+
+    #[inline]
+    fn current_player(&self) -> usize {
+        self.public_history.len() % 2
+    }
+
+    #[inline]
+    fn num_actions(&self) -> usize {
+        match self.public_history.is_empty() {
+            true => 3,
+            false => 2,
+        }
+    }
+
+    #[inline]
+    fn action_name(&self, action: Action) -> String {
+        match (self.public_history.as_slice(), action) {
+            ([], FOLD) => "Fold",
+            ([], LIMP) => "Limp",
+            ([], _) => "Shove",
+            ([LIMP], CHECK_FOLD) => "Check",
+            ([LIMP], _) => "Shove",
+            (_, CHECK_FOLD) => "Fold",
+            _ => "Call",
+        }
+        .to_string()
+    }
+
+    #[inline]
+    fn play(&self, action: usize) -> Self {
+        let mut ret = self.clone();
+        ret.public_history.push(action);
+        ret
+    }
+}
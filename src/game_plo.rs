@@ -0,0 +1,486 @@
+use crate::cards::*;
+use crate::game_push_fold::BlindStructure;
+use crate::interface::*;
+use crate::strategy::parse_parameter;
+use once_cell::sync::{Lazy, OnceCell};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::{fs::File, io::BufReader};
+
+const FOLD: usize = 0;
+const RAISE_CALL: usize = 1;
+
+/// ゲームの名前
+pub const GAME_NAME: &str = "Heads-up PLO Pot-Raise/Fold";
+
+/// PLO の手札 (4枚) の組み合わせの個数
+pub const NUM_PLO_COMBOS: usize = 270725;
+
+/// PLO の手札の同値類 (スートの入れ替えで移り合う手札の集合) の個数
+pub const NUM_PLO_CLASSES: usize = 16432;
+
+/// 勝率テーブルに格納する同値類の組 `(a, b)` (`a <= b`) の個数
+pub const NUM_PLO_CLASS_PAIRS: usize = NUM_PLO_CLASSES * (NUM_PLO_CLASSES + 1) / 2;
+
+/// 両者に PLO の手札を配る配り方の総数
+const NUM_PLO_DEALS: f64 = NUM_PLO_COMBOS as f64 * (48 * 47 * 46 * 45 / 24) as f64;
+
+/// PLO の勝率テーブルの既定のパス
+pub const DEFAULT_PLO_TABLE_PATH: &str = "static/headsup_plo_equity.bin";
+
+/// PLO の勝率テーブルのパス (未設定の場合は `DEFAULT_PLO_TABLE_PATH`)
+static PLO_TABLE_PATH: OnceCell<String> = OnceCell::new();
+
+/// 手札の同値類の名前と、属する手札 (カードの集合) の一覧
+///
+/// 手札の一覧はカードの集合を表す整数の降順に並べ、先頭をその同値類の代表とする。
+/// 同値類の間の順序も代表の降順 (AAAA が先頭) である
+struct PloClass {
+    name: String,
+    combos: Vec<u64>,
+}
+
+static PLO_CLASSES: Lazy<Vec<PloClass>> = Lazy::new(|| {
+    let perms = suit_permutations();
+    let permute = |mask: u64, perm: &[usize; 4]| {
+        (0..NUM_CARDS)
+            .filter(|&card| mask & 1 << card != 0)
+            .fold(0u64, |acc, card| {
+                acc | 1 << (4 * rank_of(card) + perm[suit_of(card)])
+            })
+    };
+
+    // スートを入れ替えた手札のうち、カードの集合を表す整数が最大のものを代表とする
+    let mut classes = HashMap::<u64, Vec<u64>>::new();
+    for a in 0..NUM_CARDS {
+        for b in (a + 1)..NUM_CARDS {
+            for c in (b + 1)..NUM_CARDS {
+                for d in (c + 1)..NUM_CARDS {
+                    let mask = 1 << a | 1 << b | 1 << c | 1 << d;
+                    let key = perms.iter().map(|perm| permute(mask, perm)).max().unwrap();
+                    classes.entry(key).or_default().push(mask);
+                }
+            }
+        }
+    }
+
+    let mut classes = classes.into_values().collect::<Vec<_>>();
+    classes
+        .iter_mut()
+        .for_each(|combos| combos.sort_unstable_by(|x, y| y.cmp(x)));
+    classes.sort_unstable_by(|x, y| y[0].cmp(&x[0]));
+    assert_eq!(classes.len(), NUM_PLO_CLASSES);
+
+    classes
+        .into_iter()
+        .map(|combos| {
+            let name = (0..NUM_CARDS)
+                .rev()
+                .filter(|&card| combos[0] & 1 << card != 0)
+                .map(card_to_string)
+                .collect();
+            PloClass { name, combos }
+        })
+        .collect()
+});
+
+/// 同値類の組毎の重複のない手札の個数と、1つ目の同値類のオールインの勝率
+///
+/// `counts[a * NUM_PLO_CLASSES + b]` は同値類 `a` の代表と重複しない同値類 `b` の手札の個数であり、
+/// スートの対称性から同値類の組 `(a, b)` の配られ方の個数は `|a| * counts[a][b]` となる。
+/// 両方の順序について展開して保持するため、メモリ上では `f32` の 16432 x 16432 の行列 (約 1.1GB) と
+/// `u8` の同じ大きさの行列 (約 270MB) を占める
+struct PloTable {
+    counts: Vec<u8>,
+    shares: Vec<f32>,
+}
+
+static PLO_TABLE: Lazy<PloTable> = Lazy::new(|| {
+    let path = PLO_TABLE_PATH
+        .get()
+        .map_or(DEFAULT_PLO_TABLE_PATH, |p| p.as_str());
+    let infile = File::open(path).unwrap_or_else(|_| panic!("could not open '{}'", path));
+    let entries = bincode::deserialize_from::<_, Vec<f32>>(BufReader::new(infile)).unwrap();
+    assert_eq!(entries.len(), NUM_PLO_CLASS_PAIRS, "invalid PLO table");
+
+    // 両方の順序について展開する
+    let mut shares = vec![0.0; NUM_PLO_CLASSES * NUM_PLO_CLASSES];
+    let mut entries = entries.into_iter();
+    for a in 0..NUM_PLO_CLASSES {
+        for b in a..NUM_PLO_CLASSES {
+            let share = entries.next().unwrap();
+            shares[a * NUM_PLO_CLASSES + b] = share;
+            shares[b * NUM_PLO_CLASSES + a] = 1.0 - share;
+        }
+    }
+
+    let counts = (0..NUM_PLO_CLASSES)
+        .into_par_iter()
+        .flat_map_iter(|a| (0..NUM_PLO_CLASSES).map(move |b| plo_disjoint_count(a, b) as u8))
+        .collect();
+
+    PloTable { counts, shares }
+});
+
+/// PLO の勝率テーブルのパスを設定する (最初に勝率テーブルが参照されるより前に呼び出す必要がある)
+pub fn set_plo_table_path(path: &str) {
+    PLO_TABLE_PATH
+        .set(path.to_string())
+        .expect("PLO table path is already set");
+}
+
+/// 同値類 `class` の名前 (代表の手札を強いカードから並べた "AsKsQhJh" など) を返す
+#[inline]
+pub fn plo_class_name(class: usize) -> &'static str {
+    &PLO_CLASSES[class].name
+}
+
+/// 同値類 `class` に属する手札 (ビット `c` がカード `c` を表すカードの集合) の一覧を返す
+///
+/// 先頭の手札が同値類の代表である
+#[inline]
+pub fn plo_class_combos(class: usize) -> &'static [u64] {
+    &PLO_CLASSES[class].combos
+}
+
+/// 同値類 `a` の代表と重複しない、同値類 `b` の手札の個数を返す
+///
+/// スートの対称性から、同値類 `a` のどの手札についても同じ値となる
+pub fn plo_disjoint_count(a: usize, b: usize) -> usize {
+    let x = plo_class_combos(a)[0];
+    plo_class_combos(b).iter().filter(|&&y| x & y == 0).count()
+}
+
+/// 同値類毎の確率 `probs` を、手札の個数で重み付けした全体の確率にする
+pub fn plo_overall(probs: &[f64]) -> f64 {
+    let weighted = probs
+        .iter()
+        .enumerate()
+        .map(|(class, prob)| prob * plo_class_combos(class).len() as f64)
+        .sum::<f64>();
+    weighted / NUM_PLO_COMBOS as f64
+}
+
+/// 同値類毎の確率 `probs` を "AsAhAdAc, AsKsQhJh:0.35" のようなレンジ表記にする
+///
+/// 確率は1%単位に丸め、0となる同値類は省略する
+pub fn format_plo_range(probs: &[f64]) -> String {
+    probs
+        .iter()
+        .enumerate()
+        .filter_map(|(class, prob)| {
+            let prob = (100.0 * prob).round() / 100.0;
+            match prob {
+                p if p <= 0.0 => None,
+                p if p >= 1.0 => Some(plo_class_name(class).to_string()),
+                p => Some(format!("{}:{}", plo_class_name(class), p)),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// ヘッズアップの PLO (ポットリミット・オマハ) のプリフロップにおいて、
+/// ポットサイズのレイズを繰り返してオールインに至るゲーム
+///
+/// スモールブラインドはフォールドかポットサイズのオープンレイズを選び、以降の各手番では
+/// フォールドかポットサイズのリレイズ (3ベット、4ベット、...) を選ぶ。レイズ後の額が有効スタックを
+/// 超える場合はオールインとなり、オールインに対してはフォールドかコールを選ぶ。
+/// フラットコールしてポストフロップに進む選択肢はない。
+/// 手札はスートの入れ替えで移り合う手札の同値類 (`PloClass` を参照) 単位で扱い、
+/// ショーダウンでは勝率テーブルの値 (同値類の組毎に無作為に選んだ配り方による推定値) を用いる
+pub struct PloGame {
+    stacks: [f64; 2],
+    blinds: BlindStructure,
+}
+
+#[derive(Clone)]
+pub struct PloNode {
+    public_history: PublicHistory,
+
+    /// 各プレイヤーのベットの額 (アンティを除く)
+    bets: [f64; 2],
+
+    /// アンティの合計
+    dead: f64,
+
+    /// 各プレイヤーが賭けられる額の上限 (アンティを除いた有効スタック)
+    cap: f64,
+}
+
+impl Game for PloGame {
+    type Node = PloNode;
+
+    #[inline]
+    fn name(&self) -> String {
+        GAME_NAME.to_string()
+    }
+
+    #[inline]
+    fn parameters(&self) -> Vec<(String, String)> {
+        let mut ret = vec![
+            ("sb_stack".to_string(), self.stacks[0].to_string()),
+            ("bb_stack".to_string(), self.stacks[1].to_string()),
+        ];
+        ret.extend(self.blinds.parameters());
+        ret
+    }
+
+    #[inline]
+    fn root(&self) -> PloNode {
+        let live = |player: usize| self.stacks[player] - self.blinds.dead(player);
+        PloNode {
+            public_history: Vec::new(),
+            bets: [self.blinds.small_blind, self.blinds.big_blind],
+            dead: self.blinds.dead(0) + self.blinds.dead(1),
+            cap: live(0).min(live(1)),
+        }
+    }
+
+    #[inline]
+    fn num_private_hands(&self) -> usize {
+        NUM_PLO_CLASSES
+    }
+
+    #[inline]
+    fn hand_name(&self, hand: usize) -> String {
+        plo_class_name(hand).to_string()
+    }
+
+    #[inline]
+    fn chance_reach(&self, pmi: &[f64]) -> Vec<f64> {
+        self.cfvalues(pmi, |_| 1.0)
+    }
+
+    #[inline]
+    fn evaluate(&self, node: &PloNode, player: usize, pmi: &Vec<f64>) -> Vec<f64> {
+        // どちらかのプレイヤーがフォールド
+        if node.public_history.last() == Some(&FOLD) {
+            let payoff = node.fold_payoff(player, &self.blinds);
+            return self.cfvalues(pmi, |_| payoff);
+        }
+
+        // オールインのショーダウン
+        self.cfvalues(pmi, |equity| {
+            self.blinds.allin_payoff(self.stacks, player, equity)
+        })
+    }
+
+    #[inline]
+    fn is_valid_deal(&self, hands: [usize; 2]) -> bool {
+        PLO_TABLE.counts[hands[0] * NUM_PLO_CLASSES + hands[1]] > 0
+    }
+
+    #[inline]
+    fn evaluate_hands(&self, node: &PloNode, player: usize, hands: [usize; 2]) -> f64 {
+        // どちらかのプレイヤーがフォールド
+        if node.public_history.last() == Some(&FOLD) {
+            return node.fold_payoff(player, &self.blinds);
+        }
+
+        // オールインのショーダウン (同値類の組について推定した勝率を用いる)
+        let index = hands[player] * NUM_PLO_CLASSES + hands[player ^ 1];
+        let equity = PLO_TABLE.shares[index] as f64;
+        self.blinds.allin_payoff(self.stacks, player, equity)
+    }
+}
+
+impl PloGame {
+    /// 両者のスタックが `effective_stack` で、既定のブラインドのゲームを作る
+    pub fn new(effective_stack: f64) -> Self {
+        Self::with_stacks([effective_stack; 2], BlindStructure::default())
+    }
+
+    /// 各プレイヤーのスタック `stacks` (0: スモールブラインド、1: ビッグブラインド) と
+    /// ブラインドとアンティの構造 `blinds` を指定するコンストラクタ
+    ///
+    /// スタックはブラインドとアンティを支払う前のもの
+    pub fn with_stacks(stacks: [f64; 2], blinds: BlindStructure) -> Self {
+        assert!(0.0 <= blinds.small_blind && blinds.small_blind <= blinds.big_blind);
+        assert!(blinds.ante >= 0.0 && blinds.bb_ante >= 0.0);
+        assert!(
+            blinds.posted(1) <= stacks[0].min(stacks[1]),
+            "effective stack must cover the blinds and antes"
+        );
+        Self { stacks, blinds }
+    }
+
+    /// `Game::parameters()` が返すパラメータの列からゲームを復元する
+    pub fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        let blinds = BlindStructure::from_parameters(parameters)?;
+        let stacks = [
+            parse_parameter(parameters, "sb_stack")?,
+            parse_parameter(parameters, "bb_stack")?,
+        ];
+        Ok(Self::with_stacks(stacks, blinds))
+    }
+
+    /// 各プレイヤーのスタック (単位: bb) を返す
+    #[inline]
+    pub fn stacks(&self) -> [f64; 2] {
+        self.stacks
+    }
+
+    /// ブラインドとアンティの構造を返す
+    #[inline]
+    pub fn blinds(&self) -> &BlindStructure {
+        &self.blinds
+    }
+
+    /// 相手の到達確率 `pmi` で重み付けして、勝率を受け取って利得を返す関数 `payoff` の値を
+    /// 同値類毎に足し合わせた counterfactual value を返す
+    fn cfvalues<F>(&self, pmi: &[f64], payoff: F) -> Vec<f64>
+    where
+        F: Fn(f64) -> f64 + Sync,
+    {
+        let table = &*PLO_TABLE;
+        (0..NUM_PLO_CLASSES)
+            .into_par_iter()
+            .map(|hand| {
+                // 配られ方の個数は `|hand| * counts[hand][b]` であり、配り方の総数で割って確率にする
+                let scale = plo_class_combos(hand).len() as f64 / NUM_PLO_DEALS;
+                let row = hand * NUM_PLO_CLASSES..(hand + 1) * NUM_PLO_CLASSES;
+                let sum = table.counts[row.clone()]
+                    .iter()
+                    .zip(&table.shares[row])
+                    .zip(pmi)
+                    .filter(|((&count, _), &p)| count > 0 && p > 0.0)
+                    .map(|((&count, &share), &p)| count as f64 * p * payoff(share as f64))
+                    .sum::<f64>();
+                scale * sum
+            })
+            .collect()
+    }
+}
+
+impl PloNode {
+    /// 現在の手番のプレイヤーがレイズした場合のレイズ後の額 (ポットサイズ、上限は有効スタック) を返す
+    #[inline]
+    fn raise_to(&self) -> f64 {
+        let player = self.current_player();
+        let opponent_bet = self.bets[player ^ 1];
+        let pot_after_call =
+            self.bets[0] + self.bets[1] + self.dead + opponent_bet - self.bets[player];
+        (opponent_bet + pot_after_call).min(self.cap)
+    }
+
+    /// 相手がオールインしており、コールかフォールドのみを選べるかどうかを返す
+    #[inline]
+    fn facing_allin(&self) -> bool {
+        self.bets[self.current_player() ^ 1] >= self.cap
+    }
+
+    /// フォールドで終わった終端ノードにおける `player` の利得を返す
+    #[inline]
+    fn fold_payoff(&self, player: usize, blinds: &BlindStructure) -> f64 {
+        let folder = (self.public_history.len() - 1) % 2;
+        let amount = self.bets[folder] + blinds.dead(folder);
+        match player == folder {
+            true => -amount,
+            false => amount,
+        }
+    }
+}
+
+impl GameNode for PloNode {
+    #[inline]
+    fn public_history(&self) -> &PublicHistory {
+        &self.public_history
+    }
+
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        match self.public_history.last() {
+            None => false,
+            Some(&FOLD) => true,
+            _ => self.bets[0] >= self.cap && self.bets[1] >= self.cap,
+        }
+    }
+
+    #[inline]
+    fn current_player(&self) -> usize {
+        self.public_history.len() % 2
+    }
+
+    #[inline]
+    fn num_actions(&self) -> usize {
+        2
+    }
+
+    #[inline]
+    fn action_name(&self, action: Action) -> String {
+        if action == FOLD {
+            return "Fold".to_string();
+        }
+        if self.facing_allin() {
+            return "Call".to_string();
+        }
+        let raise_to = self.raise_to();
+        let name = match self.public_history.len() {
+            0 => "Raise".to_string(),
+            n => format!("{}-bet", n + 2),
+        };
+        match raise_to >= self.cap {
+            true => format!("{} all-in", name),
+            false => format!("{} {}", name, raise_to),
+        }
+    }
+
+    #[inline]
+    fn play(&self, action: usize) -> Self {
+        let mut ret = self.clone();
+        if action == RAISE_CALL {
+            let player = self.current_player();
+            ret.bets[player] = match self.facing_allin() {
+                true => self.cap,
+                false => self.raise_to(),
+            };
+        }
+        ret.public_history.push(action);
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classes_partition_combos() {
+        let total = (0..NUM_PLO_CLASSES)
+            .map(|class| plo_class_combos(class).len())
+            .sum::<usize>();
+        assert_eq!(total, NUM_PLO_COMBOS);
+        assert_eq!(plo_class_name(0), "AsAhAdAc");
+        assert_eq!(plo_class_combos(0).len(), 1);
+    }
+
+    #[test]
+    fn class_name_is_canonical() {
+        let class = (0..NUM_PLO_CLASSES)
+            .find(|&class| plo_class_name(class) == "AsKsQhJh")
+            .unwrap();
+        assert_eq!(plo_class_combos(class).len(), 12);
+
+        // A-K と Q-J がスーテッドの手札と、A-Q と K-J がスーテッドの手札は区別される
+        assert!((0..NUM_PLO_CLASSES).any(|class| plo_class_name(class) == "AsKhQsJh"));
+    }
+
+    #[test]
+    fn deal_counts_are_symmetric() {
+        for (a, b) in [(0, 1), (3, 17), (100, 5000), (1234, 16431), (42, 42)] {
+            let num_deals = plo_class_combos(a)
+                .iter()
+                .map(|&x| plo_class_combos(b).iter().filter(|&&y| x & y == 0).count())
+                .sum::<usize>();
+            assert_eq!(
+                plo_class_combos(a).len() * plo_disjoint_count(a, b),
+                num_deals
+            );
+            assert_eq!(
+                plo_class_combos(b).len() * plo_disjoint_count(b, a),
+                num_deals
+            );
+        }
+    }
+}
@@ -1,21 +1,387 @@
+use crate::cards::*;
+use crate::equity_table::{decompress, is_compressed};
+use crate::icm::IcmConfig;
 use crate::interface::*;
+use crate::range::InitialRanges;
+use crate::strategy::{parse_parameter, parse_parameter_or};
 use bincode::deserialize;
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use std::{fs::File, io::Read};
 
 const FOLD: usize = 0;
 const PUSH_CALL: usize = 1;
 
-static WIN_FREQ_TABLE: Lazy<Vec<i32>> = Lazy::new(|| {
-    let path = "static/headsup_preflop_equity.bin";
+/// ゲームの名前
+pub const GAME_NAME: &str = "Heads-up Push/Fold Hold'em";
+
+/// 勝率テーブルの既定のパス
+pub const DEFAULT_TABLE_PATH: &str = "static/headsup_preflop_equity.bin";
+
+/// ショートデッキの勝率テーブルの既定のパス
+pub const DEFAULT_SHORT_DECK_TABLE_PATH: &str = "static/headsup_preflop_equity_short.bin";
+
+/// 引き分けの回数のテーブルの既定のパス
+pub const DEFAULT_TIE_TABLE_PATH: &str = "static/headsup_preflop_tie.bin";
+
+/// ショートデッキの引き分けの回数のテーブルの既定のパス
+pub const DEFAULT_SHORT_DECK_TIE_TABLE_PATH: &str = "static/headsup_preflop_tie_short.bin";
+
+/// 勝率テーブルのパス (未設定の場合は `DEFAULT_TABLE_PATH`)
+static TABLE_PATH: OnceCell<String> = OnceCell::new();
+
+/// ショートデッキの勝率テーブルのパス (未設定の場合は `DEFAULT_SHORT_DECK_TABLE_PATH`)
+static SHORT_DECK_TABLE_PATH: OnceCell<String> = OnceCell::new();
+
+/// 引き分けの回数のテーブルのパス (未設定の場合は `DEFAULT_TIE_TABLE_PATH`)
+static TIE_TABLE_PATH: OnceCell<String> = OnceCell::new();
+
+/// ショートデッキの引き分けの回数のテーブルのパス (未設定の場合は `DEFAULT_SHORT_DECK_TIE_TABLE_PATH`)
+static SHORT_DECK_TIE_TABLE_PATH: OnceCell<String> = OnceCell::new();
+
+static WIN_FREQ_TABLE: Lazy<Vec<i32>> =
+    Lazy::new(|| load_table(TABLE_PATH.get().map_or(DEFAULT_TABLE_PATH, |p| p.as_str())));
+
+static SHORT_DECK_WIN_FREQ_TABLE: Lazy<Vec<i32>> = Lazy::new(|| {
+    load_table(
+        SHORT_DECK_TABLE_PATH
+            .get()
+            .map_or(DEFAULT_SHORT_DECK_TABLE_PATH, |p| p.as_str()),
+    )
+});
+
+static TIE_FREQ_TABLE: Lazy<Vec<i32>> = Lazy::new(|| {
+    load_table(
+        TIE_TABLE_PATH
+            .get()
+            .map_or(DEFAULT_TIE_TABLE_PATH, |p| p.as_str()),
+    )
+});
+
+static SHORT_DECK_TIE_FREQ_TABLE: Lazy<Vec<i32>> = Lazy::new(|| {
+    load_table(
+        SHORT_DECK_TIE_TABLE_PATH
+            .get()
+            .map_or(DEFAULT_SHORT_DECK_TIE_TABLE_PATH, |p| p.as_str()),
+    )
+});
+
+/// 勝率テーブルを読み込む (圧縮形式と bincode による形式のどちらも受け付ける)
+fn load_table(path: &str) -> Vec<i32> {
     let mut infile = File::open(path).expect(&format!("could not open '{}'", path));
     let mut buf = Vec::new();
     infile.read_to_end(&mut buf).unwrap();
-    deserialize::<Vec<i32>>(&buf).unwrap()
-});
+    match is_compressed(&buf) {
+        true => decompress(&buf).unwrap_or_else(|e| panic!("invalid table '{}': {}", path, e)),
+        false => deserialize::<Vec<i32>>(&buf).unwrap(),
+    }
+}
+
+/// デッキ `deck` の勝率テーブルを返す
+///
+/// テーブルは手札の番号の組 (1326 x 1326 通り) で添字付けられ、各要素は全てのボードについての
+/// `2 * 勝ち + 引き分け` の回数である (デッキに含まれない手札の要素は0)
+#[inline]
+fn win_freq_table(deck: Deck) -> &'static [i32] {
+    match deck {
+        Deck::Standard => &WIN_FREQ_TABLE,
+        Deck::ShortDeck => &SHORT_DECK_WIN_FREQ_TABLE,
+    }
+}
+
+/// デッキ `deck` の引き分けの回数のテーブルを返す
+///
+/// 勝率テーブルと同じく手札の番号の組で添字付けられ、各要素は全てのボードについての引き分けの回数である
+/// (勝ちと引き分けを区別する必要のあるバウンティ付きのゲームでのみ参照する)
+#[inline]
+fn tie_freq_table(deck: Deck) -> &'static [i32] {
+    match deck {
+        Deck::Standard => &TIE_FREQ_TABLE,
+        Deck::ShortDeck => &SHORT_DECK_TIE_FREQ_TABLE,
+    }
+}
+
+/// ブラインドとアンティの構造 (単位: bb)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlindStructure {
+    /// スモールブラインド
+    pub small_blind: f64,
+
+    /// ビッグブラインド
+    pub big_blind: f64,
+
+    /// 各プレイヤーが支払うアンティ
+    pub ante: f64,
+
+    /// ビッグブラインドのプレイヤーのみが支払うアンティ (BB アンティ)
+    pub bb_ante: f64,
+}
+
+impl Default for BlindStructure {
+    fn default() -> Self {
+        Self {
+            small_blind: 0.5,
+            big_blind: 1.0,
+            ante: 0.0,
+            bb_ante: 0.0,
+        }
+    }
+}
+
+impl BlindStructure {
+    /// `player` (0: スモールブラインド、1: ビッグブラインド) がハンド開始時に支払う額を返す
+    #[inline]
+    pub fn posted(&self, player: usize) -> f64 {
+        match player {
+            0 => self.small_blind + self.ante,
+            _ => self.big_blind + self.ante + self.bb_ante,
+        }
+    }
+
+    /// `player` がハンド開始時に支払うアンティ (ポットに入るがベットには数えられない額) を返す
+    #[inline]
+    pub fn dead(&self, player: usize) -> f64 {
+        match player {
+            0 => self.ante,
+            _ => self.ante + self.bb_ante,
+        }
+    }
+
+    /// スタックが `stacks` の両者がオールインしたショーダウンにおいて、
+    /// 勝率が `equity` の `player` の利得を返す
+    ///
+    /// アンティはベットに数えられないため、両者はアンティを除いたスタックの小さい方の額を賭ける
+    #[inline]
+    pub fn allin_payoff(&self, stacks: [f64; 2], player: usize, equity: f64) -> f64 {
+        let live = (stacks[0] - self.dead(0)).min(stacks[1] - self.dead(1));
+        let pot = 2.0 * live + self.dead(0) + self.dead(1);
+        equity * pot - (live + self.dead(player))
+    }
+
+    /// `Game::parameters()` に含めるパラメータの列を返す
+    pub fn parameters(&self) -> Vec<(String, String)> {
+        vec![
+            ("small_blind".to_string(), self.small_blind.to_string()),
+            ("big_blind".to_string(), self.big_blind.to_string()),
+            ("ante".to_string(), self.ante.to_string()),
+            ("bb_ante".to_string(), self.bb_ante.to_string()),
+        ]
+    }
+
+    /// パラメータの列からブラインドとアンティの構造を復元する (存在しないものは既定値を用いる)
+    pub fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        let default = Self::default();
+        Ok(Self {
+            small_blind: parse_parameter_or(parameters, "small_blind", default.small_blind)?,
+            big_blind: parse_parameter_or(parameters, "big_blind", default.big_blind)?,
+            ante: parse_parameter_or(parameters, "ante", default.ante)?,
+            bb_ante: parse_parameter_or(parameters, "bb_ante", default.bb_ante)?,
+        })
+    }
+}
+
+impl std::fmt::Display for BlindStructure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "blinds = {}/{}", self.small_blind, self.big_blind)?;
+        if self.ante > 0.0 {
+            write!(f, ", ante = {}", self.ante)?;
+        }
+        if self.bb_ante > 0.0 {
+            write!(f, ", BB ante = {}", self.bb_ante)?;
+        }
+        Ok(())
+    }
+}
+
+/// プログレッシブノックアウト (PKO) トーナメントのバウンティの設定
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BountyConfig {
+    /// 各プレイヤー (0: スモールブラインド、1: ビッグブラインド) に懸けられたバウンティ (賞金の単位)
+    pub bounties: [f64; 2],
+
+    /// 相手をノックアウトしたときにその場で受け取るバウンティの割合
+    /// (残りは自分のバウンティに加わり、その将来の価値は考慮しない)
+    pub immediate_fraction: f64,
+
+    /// バウンティ1単位をチップに換算した価値 (単位: bb)
+    pub chips_per_bounty: f64,
+}
+
+impl BountyConfig {
+    /// `player` をノックアウトしたときにその場で受け取る賞金を返す
+    #[inline]
+    pub fn cash(&self, player: usize) -> f64 {
+        self.immediate_fraction * self.bounties[player]
+    }
+
+    /// `player` をノックアウトしたときにその場で受け取る賞金をチップに換算した価値 (単位: bb) を返す
+    #[inline]
+    pub fn chips(&self, player: usize) -> f64 {
+        self.cash(player) * self.chips_per_bounty
+    }
+
+    /// `Game::parameters()` に含めるパラメータの列を返す
+    pub fn parameters(&self) -> Vec<(String, String)> {
+        vec![
+            ("sb_bounty".to_string(), self.bounties[0].to_string()),
+            ("bb_bounty".to_string(), self.bounties[1].to_string()),
+            (
+                "bounty_immediate_fraction".to_string(),
+                self.immediate_fraction.to_string(),
+            ),
+            (
+                "chips_per_bounty".to_string(),
+                self.chips_per_bounty.to_string(),
+            ),
+        ]
+    }
 
+    /// パラメータの列から設定を復元する (`sb_bounty` が存在しない場合は `None` を返す)
+    pub fn from_parameters(parameters: &[(String, String)]) -> Result<Option<Self>, String> {
+        if !parameters.iter().any(|(k, _)| k == "sb_bounty") {
+            return Ok(None);
+        }
+        let config = Self {
+            bounties: [
+                parse_parameter(parameters, "sb_bounty")?,
+                parse_parameter(parameters, "bb_bounty")?,
+            ],
+            immediate_fraction: parse_parameter(parameters, "bounty_immediate_fraction")?,
+            chips_per_bounty: parse_parameter(parameters, "chips_per_bounty")?,
+        };
+        config.validate()?;
+        Ok(Some(config))
+    }
+
+    /// 設定が妥当かどうかを検査する
+    pub fn validate(&self) -> Result<(), String> {
+        if self.bounties.iter().any(|&b| b < 0.0) {
+            return Err("bounties must be non-negative".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.immediate_fraction) {
+            return Err("immediate bounty fraction must be between 0 and 1".to_string());
+        }
+        if self.chips_per_bounty < 0.0 {
+            return Err("chips per bounty must be non-negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for BountyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "bounties = {}/{} ({}% immediate",
+            self.bounties[0],
+            self.bounties[1],
+            100.0 * self.immediate_fraction
+        )?;
+        if self.chips_per_bounty > 0.0 {
+            write!(f, ", 1 bounty = {}[bb]", self.chips_per_bounty)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// キャッシュゲームのレーキの設定 (単位: bb)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RakeConfig {
+    /// ポットに対するレーキの割合
+    pub rate: f64,
+
+    /// レーキの上限 (0以下の場合は上限を設けない)
+    pub cap: f64,
+
+    /// フロップが配られなかったポットからはレーキを取らないか (no flop, no drop)
+    pub no_flop_no_drop: bool,
+}
+
+impl RakeConfig {
+    /// ポット `pot` から取られるレーキを返す (`flop` はフロップが配られたかどうか)
+    #[inline]
+    pub fn rake(&self, pot: f64, flop: bool) -> f64 {
+        if !flop && self.no_flop_no_drop {
+            return 0.0;
+        }
+        match self.cap > 0.0 {
+            true => (self.rate * pot).min(self.cap),
+            false => self.rate * pot,
+        }
+    }
+
+    /// `Game::parameters()` に含めるパラメータの列を返す
+    pub fn parameters(&self) -> Vec<(String, String)> {
+        vec![
+            ("rake".to_string(), self.rate.to_string()),
+            ("rake_cap".to_string(), self.cap.to_string()),
+            (
+                "no_flop_no_drop".to_string(),
+                self.no_flop_no_drop.to_string(),
+            ),
+        ]
+    }
+
+    /// パラメータの列から設定を復元する (`rake` が存在しない場合は `None` を返す)
+    pub fn from_parameters(parameters: &[(String, String)]) -> Result<Option<Self>, String> {
+        if !parameters.iter().any(|(k, _)| k == "rake") {
+            return Ok(None);
+        }
+        let config = Self {
+            rate: parse_parameter(parameters, "rake")?,
+            cap: parse_parameter_or(parameters, "rake_cap", 0.0)?,
+            no_flop_no_drop: parse_parameter_or(parameters, "no_flop_no_drop", true)?,
+        };
+        config.validate()?;
+        Ok(Some(config))
+    }
+
+    /// 設定が妥当かどうかを検査する
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..1.0).contains(&self.rate) {
+            return Err("rake must be at least 0 and less than 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for RakeConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "rake = {}%", 100.0 * self.rate)?;
+        if self.cap > 0.0 {
+            write!(f, " (cap = {}[bb])", self.cap)?;
+        }
+        if !self.no_flop_no_drop {
+            write!(f, ", raked without flop")?;
+        }
+        Ok(())
+    }
+}
+
+/// スモールブラインドがフォールドかオールインを選ぶヘッズアップのプリフロップ
+///
+/// `game_tree::TreeConfig::push_fold()` で構築したツリーと同じゲームを、ツリーを構築せずに直接表す
 pub struct PushFoldGame {
-    effective_stack: f64,
+    stacks: [f64; 2],
+    blinds: BlindStructure,
+    icm: Option<IcmConfig>,
+    bounty: Option<BountyConfig>,
+    rake: Option<RakeConfig>,
+    deck: Deck,
+    ranges: InitialRanges,
+    payoffs: OnceCell<Payoffs>,
+}
+
+/// 終端履歴における各プレイヤーの利得
+///
+/// ICM (特に Monte Carlo 法による近似) の計算を終端履歴の評価毎に繰り返さないよう、
+/// 最初に必要になった時点で1度だけ計算して保持する
+#[derive(Clone, Copy, Debug)]
+struct Payoffs {
+    /// `showdown[player]`: ショーダウンで `player` が勝った場合、引き分けた場合、負けた場合の利得
+    showdown: [(f64, f64, f64); 2],
+
+    /// `fold[folder][player]`: `folder` がフォールドした場合の `player` の利得
+    fold: [[f64; 2]; 2],
 }
 
 #[derive(Clone)]
@@ -27,90 +393,520 @@ impl Game for PushFoldGame {
     type Node = PushFoldNode;
 
     #[inline]
-    fn root() -> PushFoldNode {
+    fn name(&self) -> String {
+        GAME_NAME.to_string()
+    }
+
+    #[inline]
+    fn parameters(&self) -> Vec<(String, String)> {
+        let mut ret = vec![
+            ("sb_stack".to_string(), self.stacks[0].to_string()),
+            ("bb_stack".to_string(), self.stacks[1].to_string()),
+        ];
+        ret.extend(self.blinds.parameters());
+        if let Some(icm) = &self.icm {
+            ret.extend(icm.parameters());
+        }
+        if let Some(bounty) = &self.bounty {
+            ret.extend(bounty.parameters());
+        }
+        if let Some(rake) = &self.rake {
+            ret.extend(rake.parameters());
+        }
+        if self.deck != Deck::Standard {
+            ret.push(("deck".to_string(), self.deck.to_string()));
+        }
+        ret.extend(self.ranges.parameters());
+        ret
+    }
+
+    #[inline]
+    fn root(&self) -> PushFoldNode {
         PushFoldNode {
             public_history: Vec::new(),
         }
     }
 
     #[inline]
-    fn num_private_hands() -> usize {
-        52 * 51 / 2
+    fn num_private_hands(&self) -> usize {
+        NUM_COMBOS
     }
 
     #[inline]
-    fn evaluate(&self, node: &PushFoldNode, player: usize, pmi: &Vec<f64>) -> Vec<f64> {
-        let num_hands_inv = (2. * 2.) / (52. * 51. * 50. * 49.);
-        let num_board = (48 * 47 * 46 * 45 * 44) / (5 * 4 * 3 * 2);
-        let num_board_inv = 1.0 / num_board as f64;
+    fn initial_reach(&self, player: usize) -> Vec<f64> {
+        // デッキに含まれない手札は配られない
+        let mut ret = self.ranges.get(player).to_vec();
+        for (hand, reach) in ret.iter_mut().enumerate() {
+            if !self.deck.contains_combo(hand) {
+                *reach = 0.0;
+            }
+        }
+        ret
+    }
+
+    #[inline]
+    fn hand_name(&self, hand: usize) -> String {
+        combo_to_string(hand)
+    }
+
+    #[inline]
+    fn chance_reach(&self, pmi: &[f64]) -> Vec<f64> {
+        holdem_chance_reach(self.deck, pmi)
+    }
 
+    #[inline]
+    fn evaluate(&self, node: &PushFoldNode, player: usize, pmi: &Vec<f64>) -> Vec<f64> {
         // どちらかのプレイヤーがフォールド
         if node.public_history.last() == Some(&FOLD) {
-            let pmi_sum = pmi.iter().sum::<f64>();
-            let mut pmi_sum_ex = [0.0; 52];
-
-            let mut k = 0;
-            for i in 0..51 {
-                for j in (i + 1)..52 {
-                    pmi_sum_ex[i] += pmi[k];
-                    pmi_sum_ex[j] += pmi[k];
-                    k += 1;
+            let payoff = self.fold_payoff(node, player);
+            let mut ret = self.chance_reach(pmi);
+            ret.iter_mut().for_each(|el| *el *= payoff);
+            return ret;
+        }
+
+        // ショーダウン
+        match self.is_zero_sum() {
+            true => showdown_cfvalues(self.deck, pmi, |equity| {
+                self.blinds.allin_payoff(self.stacks, player, equity)
+            }),
+            false => {
+                let (win, tie, lose) = self.showdown_utilities(player);
+                match self.distinguishes_ties() {
+                    true => showdown_cfvalues_with_ties(self.deck, pmi, |win_prob, tie_prob| {
+                        win_prob * win + tie_prob * tie + (1.0 - win_prob - tie_prob) * lose
+                    }),
+                    false => showdown_cfvalues(self.deck, pmi, |equity| {
+                        equity * win + (1.0 - equity) * lose
+                    }),
                 }
             }
+        }
+    }
 
-            let payoff = match node.public_history.len() {
-                1 => [-0.5, 0.5][player],
-                _ => [1.0, -1.0][player],
-            } * num_hands_inv;
-
-            let mut k = 0;
-            let mut ret = Vec::with_capacity(Self::num_private_hands());
-            for i in 0..51 {
-                for j in (i + 1)..52 {
-                    // 包除原理
-                    ret.push(payoff * (pmi_sum - pmi_sum_ex[i] - pmi_sum_ex[j] + pmi[k]));
-                    k += 1;
+    #[inline]
+    fn is_valid_deal(&self, hands: [usize; 2]) -> bool {
+        combos_disjoint(hands[0], hands[1])
+            && self.deck.contains_combo(hands[0])
+            && self.deck.contains_combo(hands[1])
+    }
+
+    #[inline]
+    fn evaluate_hands(&self, node: &PushFoldNode, player: usize, hands: [usize; 2]) -> f64 {
+        // どちらかのプレイヤーがフォールド
+        if node.public_history.last() == Some(&FOLD) {
+            return self.fold_payoff(node, player);
+        }
+
+        // ショーダウン (ボードについての期待値)
+        let hands = [hands[player], hands[player ^ 1]];
+        let equity = equity(self.deck, hands);
+        match self.is_zero_sum() {
+            true => self.blinds.allin_payoff(self.stacks, player, equity),
+            false => {
+                let (win, tie, lose) = self.showdown_utilities(player);
+                match self.distinguishes_ties() {
+                    true => {
+                        let (win_prob, tie_prob) = win_tie_probabilities(self.deck, hands);
+                        win_prob * win + tie_prob * tie + (1.0 - win_prob - tie_prob) * lose
+                    }
+                    false => equity * win + (1.0 - equity) * lose,
                 }
             }
+        }
+    }
+}
 
-            return ret;
+/// 2枚の手札の組 (1326通り) についての `Game::chance_reach()` の実装
+///
+/// デッキ `deck` に含まれない手札については0を返す
+pub(crate) fn holdem_chance_reach(deck: Deck, pmi: &[f64]) -> Vec<f64> {
+    let num_cards = deck.num_cards() as f64;
+    let num_hands_inv =
+        (2. * 2.) / (num_cards * (num_cards - 1.) * (num_cards - 2.) * (num_cards - 3.));
+    let pmi_sum = pmi.iter().sum::<f64>();
+    let mut pmi_sum_ex = [0.0; NUM_CARDS];
+
+    let mut k = 0;
+    for i in 0..(NUM_CARDS - 1) {
+        for j in (i + 1)..NUM_CARDS {
+            pmi_sum_ex[i] += pmi[k];
+            pmi_sum_ex[j] += pmi[k];
+            k += 1;
         }
+    }
 
-        // ショーダウン
-        let mut k = 0;
-        let mut ret = Vec::with_capacity(Self::num_private_hands());
-        for i in 0..51 {
-            for j in (i + 1)..52 {
-                let k_start = k;
-                let mut cfvalue = 0.0;
-                for m in 0..51 {
-                    if i == m || j == m {
-                        k += 51 - m;
-                        continue;
-                    }
-                    for n in (m + 1)..52 {
-                        if i == n || j == n {
-                            k += 1;
-                            continue;
-                        }
-                        let win_freq = WIN_FREQ_TABLE[k];
-                        let lose_freq = 2 * num_board - win_freq;
-                        let ev = self.effective_stack * (win_freq - lose_freq) as f64;
-                        cfvalue += ev * pmi[k - k_start];
+    let mut k = 0;
+    let mut ret = Vec::with_capacity(NUM_COMBOS);
+    for i in 0..(NUM_CARDS - 1) {
+        for j in (i + 1)..NUM_CARDS {
+            // 包除原理
+            ret.push(match deck.contains(i) {
+                true => num_hands_inv * (pmi_sum - pmi_sum_ex[i] - pmi_sum_ex[j] + pmi[k]),
+                false => 0.0,
+            });
+            k += 1;
+        }
+    }
+
+    ret
+}
+
+/// オールインのショーダウンにおける counterfactual value を計算する
+///
+/// `payoff` は (ボードについて平均した) 勝率を受け取って利得を返す関数
+pub(crate) fn showdown_cfvalues<F: Fn(f64) -> f64>(deck: Deck, pmi: &[f64], payoff: F) -> Vec<f64> {
+    let equity_scale = 0.5 / deck.num_boards() as f64;
+    let table = win_freq_table(deck);
+    showdown_cfvalues_by_index(deck, pmi, |k| payoff(table[k] as f64 * equity_scale))
+}
+
+/// オールインのショーダウンにおける counterfactual value を、勝ちと引き分けを区別して計算する
+///
+/// `payoff` は (ボードについて平均した) 勝ちと引き分けの確率を受け取って利得を返す関数
+pub(crate) fn showdown_cfvalues_with_ties<F: Fn(f64, f64) -> f64>(
+    deck: Deck,
+    pmi: &[f64],
+    payoff: F,
+) -> Vec<f64> {
+    let scale = 1.0 / deck.num_boards() as f64;
+    let (table, ties) = (win_freq_table(deck), tie_freq_table(deck));
+    showdown_cfvalues_by_index(deck, pmi, |k| {
+        let win = (table[k] - ties[k]) / 2;
+        payoff(win as f64 * scale, ties[k] as f64 * scale)
+    })
+}
+
+/// オールインのショーダウンにおける counterfactual value を計算するヘルパー
+///
+/// `payoff` は手札の組の (勝率テーブルにおける) 添字を受け取って利得を返す関数
+#[inline]
+fn showdown_cfvalues_by_index<F: Fn(usize) -> f64>(deck: Deck, pmi: &[f64], payoff: F) -> Vec<f64> {
+    let num_cards = deck.num_cards() as f64;
+    let num_hands_inv =
+        (2. * 2.) / (num_cards * (num_cards - 1.) * (num_cards - 2.) * (num_cards - 3.));
+
+    let mut k = 0;
+    let mut ret = Vec::with_capacity(NUM_COMBOS);
+    for i in 0..(NUM_CARDS - 1) {
+        for j in (i + 1)..NUM_CARDS {
+            // デッキに含まれない手札 (カード `i` が含まれなければ `j` 以下のカードも同様)
+            if !deck.contains(i) {
+                ret.push(0.0);
+                k += NUM_COMBOS;
+                continue;
+            }
+            let k_start = k;
+            let mut cfvalue = 0.0;
+            for m in 0..(NUM_CARDS - 1) {
+                if i == m || j == m {
+                    k += NUM_CARDS - 1 - m;
+                    continue;
+                }
+                for n in (m + 1)..NUM_CARDS {
+                    if i == n || j == n {
                         k += 1;
+                        continue;
                     }
+                    cfvalue += payoff(k) * pmi[k - k_start];
+                    k += 1;
                 }
-                ret.push(num_hands_inv * (0.5 * num_board_inv) * cfvalue);
             }
+            ret.push(num_hands_inv * cfvalue);
         }
-
-        ret
     }
+
+    ret
+}
+
+/// デッキ `deck` において、手札 `hands[0]` の手札 `hands[1]` に対する (ボードについて平均した)
+/// 勝率を返す
+#[inline]
+pub(crate) fn equity(deck: Deck, hands: [usize; 2]) -> f64 {
+    let win_freq = win_freq_table(deck)[hands[0] * NUM_COMBOS + hands[1]];
+    win_freq as f64 / (2 * deck.num_boards()) as f64
+}
+
+/// デッキ `deck` において、手札 `hands[0]` が手札 `hands[1]` に勝つ確率と引き分ける確率を
+/// (ボードについて平均して) 返す
+#[inline]
+pub(crate) fn win_tie_probabilities(deck: Deck, hands: [usize; 2]) -> (f64, f64) {
+    let k = hands[0] * NUM_COMBOS + hands[1];
+    let (win_freq, tie_freq) = (win_freq_table(deck)[k], tie_freq_table(deck)[k]);
+    let num_boards = deck.num_boards() as f64;
+    (
+        ((win_freq - tie_freq) / 2) as f64 / num_boards,
+        tie_freq as f64 / num_boards,
+    )
+}
+
+/// 勝率テーブルのパスを設定する (最初に勝率テーブルが参照されるより前に呼び出す必要がある)
+pub fn set_table_path(path: &str) {
+    TABLE_PATH
+        .set(path.to_string())
+        .expect("table path is already set");
+}
+
+/// ショートデッキの勝率テーブルのパスを設定する
+/// (最初に勝率テーブルが参照されるより前に呼び出す必要がある)
+pub fn set_short_deck_table_path(path: &str) {
+    SHORT_DECK_TABLE_PATH
+        .set(path.to_string())
+        .expect("short-deck table path is already set");
+}
+
+/// 引き分けの回数のテーブルのパスを設定する
+/// (最初にテーブルが参照されるより前に呼び出す必要がある)
+pub fn set_tie_table_path(path: &str) {
+    TIE_TABLE_PATH
+        .set(path.to_string())
+        .expect("tie table path is already set");
+}
+
+/// ショートデッキの引き分けの回数のテーブルのパスを設定する
+/// (最初にテーブルが参照されるより前に呼び出す必要がある)
+pub fn set_short_deck_tie_table_path(path: &str) {
+    SHORT_DECK_TIE_TABLE_PATH
+        .set(path.to_string())
+        .expect("short-deck tie table path is already set");
 }
 
 impl PushFoldGame {
     pub fn new(effective_stack: f64) -> Self {
-        PushFoldGame { effective_stack }
+        Self::with_blinds(effective_stack, BlindStructure::default())
+    }
+
+    /// ブラインドとアンティの構造 `blinds` を指定するコンストラクタ
+    ///
+    /// 有効スタックはブラインドとアンティを支払う前のスタック
+    pub fn with_blinds(effective_stack: f64, blinds: BlindStructure) -> Self {
+        Self::with_stacks([effective_stack, effective_stack], blinds)
+    }
+
+    /// 各プレイヤーのスタック `stacks` (0: スモールブラインド、1: ビッグブラインド) を指定するコンストラクタ
+    ///
+    /// スタックはブラインドとアンティを支払う前のもので、ショーダウンでは両者が
+    /// 小さい方のスタック (有効スタック) の額を賭ける (BB アンティがある場合はその分だけ少ない)
+    pub fn with_stacks(stacks: [f64; 2], blinds: BlindStructure) -> Self {
+        assert!(0.0 <= blinds.small_blind && blinds.small_blind <= blinds.big_blind);
+        assert!(blinds.ante >= 0.0 && blinds.bb_ante >= 0.0);
+        assert!(
+            blinds.posted(1) <= stacks[0].min(stacks[1]),
+            "effective stack must cover the blinds and antes"
+        );
+        PushFoldGame {
+            stacks,
+            blinds,
+            icm: None,
+            bounty: None,
+            rake: None,
+            deck: Deck::Standard,
+            ranges: InitialRanges::default(),
+            payoffs: OnceCell::new(),
+        }
+    }
+
+    /// `Game::parameters()` が返すパラメータの列からゲームを復元する
+    ///
+    /// ブラインドとアンティのパラメータが存在しない場合は既定値を用いる。
+    /// また、各プレイヤーのスタックの代わりに `effective_stack` のみを持つ形式も受け付ける
+    pub fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        let blinds = BlindStructure::from_parameters(parameters)?;
+        let stacks = match parameters.iter().any(|(k, _)| k == "effective_stack") {
+            true => [parse_parameter(parameters, "effective_stack")?; 2],
+            false => [
+                parse_parameter(parameters, "sb_stack")?,
+                parse_parameter(parameters, "bb_stack")?,
+            ],
+        };
+        let mut ret = Self::with_stacks(stacks, blinds);
+        if let Some(icm) = IcmConfig::from_parameters(parameters)? {
+            ret.set_icm(icm);
+        }
+        if let Some(bounty) = BountyConfig::from_parameters(parameters)? {
+            ret.set_bounty(bounty);
+        }
+        if let Some(rake) = RakeConfig::from_parameters(parameters)? {
+            ret.set_rake(rake);
+        }
+        ret.set_deck(parse_parameter_or(parameters, "deck", Deck::Standard)?);
+        ret.ranges = InitialRanges::from_parameters(parameters)?;
+        Ok(ret)
+    }
+
+    /// ブラインドとアンティの構造を返す
+    #[inline]
+    pub fn blinds(&self) -> &BlindStructure {
+        &self.blinds
+    }
+
+    /// 利得を ICM によるトーナメントエクイティの増減とする (`icm` の賞金の単位で表される)
+    pub fn set_icm(&mut self, icm: IcmConfig) {
+        self.icm = Some(icm);
+        self.payoffs = OnceCell::new();
+    }
+
+    /// ICM の設定を返す (利得がチップの増減の場合は `None`)
+    #[inline]
+    pub fn icm(&self) -> Option<&IcmConfig> {
+        self.icm.as_ref()
+    }
+
+    /// ショーダウンで相手をノックアウトした場合にバウンティを受け取るようにする
+    ///
+    /// ICM を用いる場合はバウンティを賞金の単位のまま、用いない場合はチップに換算して利得に加える
+    pub fn set_bounty(&mut self, bounty: BountyConfig) {
+        self.bounty = Some(bounty);
+        self.payoffs = OnceCell::new();
+    }
+
+    /// バウンティの設定を返す
+    #[inline]
+    pub fn bounty(&self) -> Option<&BountyConfig> {
+        self.bounty.as_ref()
+    }
+
+    /// 用いるデッキを設定する (ショートデッキでは2から5を含む手札は配られない)
+    pub fn set_deck(&mut self, deck: Deck) {
+        self.deck = deck;
+    }
+
+    /// 用いるデッキを返す
+    #[inline]
+    pub fn deck(&self) -> Deck {
+        self.deck
+    }
+
+    /// 勝ったプレイヤーが受け取るポットからレーキを取るようにする
+    pub fn set_rake(&mut self, rake: RakeConfig) {
+        self.rake = Some(rake);
+        self.payoffs = OnceCell::new();
+    }
+
+    /// レーキの設定を返す
+    #[inline]
+    pub fn rake(&self) -> Option<&RakeConfig> {
+        self.rake.as_ref()
+    }
+
+    /// 利得がチップの増減そのものでゼロサムであるか (ICM・バウンティ・レーキのいずれも
+    /// 用いないか) を返す
+    #[inline]
+    pub fn is_zero_sum(&self) -> bool {
+        self.icm.is_none() && self.bounty.is_none() && self.rake.is_none()
+    }
+
+    /// ショーダウンの利得の計算で勝ちと引き分けを区別する必要があるか (ICM またはバウンティを
+    /// 用いるか) を返す
+    ///
+    /// ICM は凹であり、バウンティは勝った場合のみ受け取るため、引き分けの利得は勝ちと負けの
+    /// 利得の平均とは一致しない。チップの増減とレーキのみの場合は一致するため、勝率だけで計算できる
+    #[inline]
+    pub fn distinguishes_ties(&self) -> bool {
+        self.icm.is_some() || self.bounty.is_some()
+    }
+
+    /// 各プレイヤーのチップの増減 `chips` を `player` の利得に変換する
+    #[inline]
+    fn utility(&self, player: usize, chips: [f64; 2]) -> f64 {
+        match &self.icm {
+            None => chips[player],
+            Some(icm) => icm.utility(&self.stacks, &chips, player),
+        }
+    }
+
+    /// 終端履歴における各プレイヤーの利得を返す (最初の呼び出しで計算する)
+    #[inline]
+    fn payoffs(&self) -> &Payoffs {
+        self.payoffs.get_or_init(|| Payoffs {
+            showdown: [0, 1].map(|player| self.compute_showdown_utilities(player)),
+            fold: [0, 1]
+                .map(|folder| [0, 1].map(|player| self.compute_fold_payoff(folder, player))),
+        })
+    }
+
+    /// ショーダウンで `player` が勝った場合、引き分けた場合、負けた場合の利得を返す
+    #[inline]
+    fn showdown_utilities(&self, player: usize) -> (f64, f64, f64) {
+        self.payoffs().showdown[player]
+    }
+
+    /// ショーダウンで `player` が勝った場合、引き分けた場合、負けた場合の利得を計算する
+    ///
+    /// 引き分けではポット (レーキを引いたもの) を等分する。相手のスタックをカバーしていれば、
+    /// 勝った場合に限り相手をノックアウトしてバウンティを受け取る
+    fn compute_showdown_utilities(&self, player: usize) -> (f64, f64, f64) {
+        let opponent = player ^ 1;
+        let win = self.blinds.allin_payoff(self.stacks, player, 1.0);
+        let lose = self.blinds.allin_payoff(self.stacks, player, 0.0);
+        let rake = self.rake.map_or(0.0, |rake| rake.rake(win - lose, true));
+        let mut win_chips = [0.0; 2];
+        win_chips[player] = win - rake;
+        win_chips[opponent] = self.blinds.allin_payoff(self.stacks, opponent, 0.0);
+        let mut lose_chips = [0.0; 2];
+        lose_chips[player] = lose;
+        lose_chips[opponent] = self.blinds.allin_payoff(self.stacks, opponent, 1.0) - rake;
+        let mut tie_chips = [0.0; 2];
+        tie_chips[player] = self.blinds.allin_payoff(self.stacks, player, 0.5) - 0.5 * rake;
+        tie_chips[opponent] = self.blinds.allin_payoff(self.stacks, opponent, 0.5) - 0.5 * rake;
+
+        let bounty = match &self.bounty {
+            Some(bounty) if self.stacks[opponent] <= self.stacks[player] => match &self.icm {
+                None => bounty.chips(opponent),
+                Some(_) => bounty.cash(opponent),
+            },
+            _ => 0.0,
+        };
+        (
+            self.utility(player, win_chips) + bounty,
+            self.utility(player, tie_chips),
+            self.utility(player, lose_chips),
+        )
+    }
+
+    /// フォールドで終わった終端ノード `node` における `player` の利得を返す
+    #[inline]
+    fn fold_payoff(&self, node: &PushFoldNode, player: usize) -> f64 {
+        let folder = node.public_history.len() - 1;
+        self.payoffs().fold[folder][player]
+    }
+
+    /// `folder` がフォールドした場合の `player` の利得を計算する
+    fn compute_fold_payoff(&self, folder: usize, player: usize) -> f64 {
+        // フォールドしたプレイヤーは支払い済みのブラインドとアンティを失い、
+        // 相手はそこからフロップが配られなかった場合のレーキを引いた額を得る
+        let posted = self.blinds.posted(folder);
+        let pot = self.blinds.posted(0) + self.blinds.posted(1);
+        let rake = self.rake.map_or(0.0, |rake| rake.rake(pot, false));
+        let mut chips = [posted - rake; 2];
+        chips[folder] = -posted;
+        self.utility(player, chips)
+    }
+
+    /// 各プレイヤーのスタック (単位: bb) を返す
+    #[inline]
+    pub fn stacks(&self) -> [f64; 2] {
+        self.stacks
+    }
+
+    /// 有効スタック (単位: bb) を返す
+    #[inline]
+    pub fn effective_stack(&self) -> f64 {
+        self.stacks[0].min(self.stacks[1])
+    }
+
+    /// 利得の期待値 `ev` から `player` のハンド終了時のスタックの期待値を返す
+    /// (ICM を用いる場合はトーナメントエクイティの期待値を返す)
+    #[inline]
+    pub fn expected_stack(&self, player: usize, ev: f64) -> f64 {
+        match &self.icm {
+            None => self.stacks[player] + ev,
+            Some(icm) => icm.equities(&self.stacks)[player] + ev,
+        }
+    }
+
+    /// `player` の初期レンジを設定する (`range` の各要素は手札が実現している確率)
+    pub fn set_initial_range(&mut self, player: usize, range: Vec<f64>) {
+        self.ranges.set(player, range);
     }
 }
 
@@ -139,6 +935,16 @@ impl GameNode for PushFoldNode {
         2
     }
 
+    #[inline]
+    fn action_name(&self, action: Action) -> String {
+        match (self.public_history.as_slice(), action) {
+            (_, FOLD) => "Fold",
+            ([], _) => "Push",
+            _ => "Call",
+        }
+        .to_string()
+    }
+
     #[inline]
     fn play(&self, action: usize) -> Self {
         let mut ret = self.clone();
@@ -146,3 +952,41 @@ impl GameNode for PushFoldNode {
         ret
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icm_tie_differs_from_average() {
+        let mut game = PushFoldGame::with_stacks([10.0, 10.0], BlindStructure::default());
+        game.set_icm(IcmConfig {
+            payouts: vec![50.0, 30.0, 20.0],
+            other_stacks: vec![15.0],
+        });
+        assert!(game.distinguishes_ties());
+
+        // 引き分けではスタックが変わらないため利得は0だが、勝ちと負けの平均は負となる
+        for player in 0..2 {
+            let (win, tie, lose) = game.showdown_utilities(player);
+            assert!(tie.abs() < 1e-9, "{}", tie);
+            assert!(0.5 * (win + lose) < -0.1, "{} {}", win, lose);
+        }
+    }
+
+    #[test]
+    fn rake_tie_equals_average() {
+        let mut game = PushFoldGame::with_stacks([10.0, 15.0], BlindStructure::default());
+        game.set_rake(RakeConfig {
+            rate: 0.05,
+            cap: 3.0,
+            no_flop_no_drop: true,
+        });
+        assert!(!game.distinguishes_ties());
+
+        for player in 0..2 {
+            let (win, tie, lose) = game.showdown_utilities(player);
+            assert!((tie - 0.5 * (win + lose)).abs() < 1e-9);
+        }
+    }
+}
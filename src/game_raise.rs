@@ -0,0 +1,361 @@
+use crate::cards::*;
+use crate::game_limp::realized_share;
+use crate::game_push_fold::*;
+use crate::interface::*;
+use crate::range::InitialRanges;
+use crate::strategy::{parse_parameter, parse_parameter_or};
+
+// 共通のアクション
+const FOLD: usize = 0;
+const CALL: usize = 1;
+
+// オープンレイズに対するビッグブラインドのアクション
+const THREE_BET_SHOVE: usize = 2;
+
+/// ゲームの名前
+pub const GAME_NAME: &str = "Heads-up Raise/3-bet Shove Hold'em";
+
+/// スモールブラインドがフォールド・オープンレイズ・オールインを選び、
+/// ビッグブラインドがオープンレイズに対してフォールド・コール・3ベットオールインを選べる
+/// ヘッズアップのプリフロップ
+///
+/// オープンレイズがコールされた場合はポストフロップに進む代わりに、
+/// 勝率に各プレイヤーのエクイティ実現率を掛けた値の比でポットを分け合うものとみなす
+pub struct RaiseGame {
+    stacks: [f64; 2],
+    blinds: BlindStructure,
+    raise_sizes: Vec<f64>,
+    realization: [f64; 2],
+    ranges: InitialRanges,
+}
+
+#[derive(Clone)]
+pub struct RaiseNode {
+    public_history: PublicHistory,
+    raise_sizes: Vec<f64>,
+}
+
+/// 終端ノードの種類
+enum Terminal {
+    /// `player` がポットに `amount` を入れた後にフォールドした
+    Fold { player: usize, amount: f64 },
+
+    /// オールインのショーダウン
+    AllIn,
+
+    /// オープンレイズ (レイズ後の額 `amount`) がコールされ、ポストフロップに進んだ
+    Called { amount: f64 },
+}
+
+impl Game for RaiseGame {
+    type Node = RaiseNode;
+
+    #[inline]
+    fn name(&self) -> String {
+        GAME_NAME.to_string()
+    }
+
+    #[inline]
+    fn parameters(&self) -> Vec<(String, String)> {
+        let raise_sizes = self
+            .raise_sizes
+            .iter()
+            .map(|size| size.to_string())
+            .collect::<Vec<_>>();
+        let mut ret = vec![
+            ("sb_stack".to_string(), self.stacks[0].to_string()),
+            ("bb_stack".to_string(), self.stacks[1].to_string()),
+        ];
+        ret.extend(self.blinds.parameters());
+        ret.extend([
+            ("raise_sizes".to_string(), raise_sizes.join(",")),
+            (
+                "sb_realization".to_string(),
+                self.realization[0].to_string(),
+            ),
+            (
+                "bb_realization".to_string(),
+                self.realization[1].to_string(),
+            ),
+        ]);
+        ret.extend(self.ranges.parameters());
+        ret
+    }
+
+    #[inline]
+    fn root(&self) -> RaiseNode {
+        RaiseNode {
+            public_history: Vec::new(),
+            raise_sizes: self.raise_sizes.clone(),
+        }
+    }
+
+    #[inline]
+    fn num_private_hands(&self) -> usize {
+        NUM_COMBOS
+    }
+
+    #[inline]
+    fn initial_reach(&self, player: usize) -> Vec<f64> {
+        self.ranges.get(player).to_vec()
+    }
+
+    #[inline]
+    fn hand_name(&self, hand: usize) -> String {
+        combo_to_string(hand)
+    }
+
+    #[inline]
+    fn chance_reach(&self, pmi: &[f64]) -> Vec<f64> {
+        holdem_chance_reach(Deck::Standard, pmi)
+    }
+
+    #[inline]
+    fn evaluate(&self, node: &RaiseNode, player: usize, pmi: &Vec<f64>) -> Vec<f64> {
+        match self.terminal(node) {
+            Terminal::Fold {
+                player: folder,
+                amount,
+            } => {
+                let payoff = if player == folder { -amount } else { amount };
+                let mut ret = self.chance_reach(pmi);
+                ret.iter_mut().for_each(|el| *el *= payoff);
+                ret
+            }
+            Terminal::AllIn => showdown_cfvalues(Deck::Standard, pmi, |equity| {
+                self.blinds.allin_payoff(self.stacks, player, equity)
+            }),
+            Terminal::Called { amount } => showdown_cfvalues(Deck::Standard, pmi, |equity| {
+                self.called_payoff(player, amount, equity)
+            }),
+        }
+    }
+
+    #[inline]
+    fn is_valid_deal(&self, hands: [usize; 2]) -> bool {
+        combos_disjoint(hands[0], hands[1])
+    }
+
+    #[inline]
+    fn evaluate_hands(&self, node: &RaiseNode, player: usize, hands: [usize; 2]) -> f64 {
+        let equity = || equity(Deck::Standard, [hands[player], hands[player ^ 1]]);
+        match self.terminal(node) {
+            Terminal::Fold {
+                player: folder,
+                amount,
+            } => match player == folder {
+                true => -amount,
+                false => amount,
+            },
+            Terminal::AllIn => self.blinds.allin_payoff(self.stacks, player, equity()),
+            Terminal::Called { amount } => self.called_payoff(player, amount, equity()),
+        }
+    }
+}
+
+impl RaiseGame {
+    /// 各プレイヤーのスタック `stacks`、ブラインドとアンティの構造 `blinds`、
+    /// オープンレイズの額 (レイズ後の額) の一覧 `raise_sizes`、
+    /// コール後のエクイティ実現率 `realization` を指定するコンストラクタ
+    ///
+    /// スタックはブラインドとアンティを支払う前のもので、
+    /// オールインのショーダウンでは両者がアンティを除いた有効スタックの額を賭ける
+    pub fn new(
+        stacks: [f64; 2],
+        blinds: BlindStructure,
+        raise_sizes: Vec<f64>,
+        realization: [f64; 2],
+    ) -> Self {
+        let effective_stack = stacks[0].min(stacks[1]);
+        assert!(0.0 <= blinds.small_blind && blinds.small_blind <= blinds.big_blind);
+        assert!(blinds.ante >= 0.0 && blinds.bb_ante >= 0.0);
+        assert!(
+            blinds.posted(1) <= effective_stack,
+            "effective stack must cover the blinds and antes"
+        );
+        assert!(!raise_sizes.is_empty());
+        assert!(
+            raise_sizes
+                .iter()
+                .all(|&size| blinds.big_blind < size && size + blinds.ante < effective_stack),
+            "raise sizes must be greater than the big blind and less than the effective stack"
+        );
+        assert!(realization.iter().all(|&r| r > 0.0));
+        Self {
+            stacks,
+            blinds,
+            raise_sizes,
+            realization,
+            ranges: InitialRanges::default(),
+        }
+    }
+
+    /// `Game::parameters()` が返すパラメータの列からゲームを復元する
+    pub fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        let blinds = BlindStructure::from_parameters(parameters)?;
+        let stacks = [
+            parse_parameter(parameters, "sb_stack")?,
+            parse_parameter(parameters, "bb_stack")?,
+        ];
+        let raise_sizes =
+            parse_raise_sizes(&parse_parameter::<String>(parameters, "raise_sizes")?)?;
+        let realization = [
+            parse_parameter_or(parameters, "sb_realization", 1.0)?,
+            parse_parameter_or(parameters, "bb_realization", 1.0)?,
+        ];
+        let mut ret = Self::new(stacks, blinds, raise_sizes, realization);
+        ret.ranges = InitialRanges::from_parameters(parameters)?;
+        Ok(ret)
+    }
+
+    /// 各プレイヤーのスタック (単位: bb) を返す
+    #[inline]
+    pub fn stacks(&self) -> [f64; 2] {
+        self.stacks
+    }
+
+    /// 有効スタック (単位: bb) を返す
+    #[inline]
+    pub fn effective_stack(&self) -> f64 {
+        self.stacks[0].min(self.stacks[1])
+    }
+
+    /// ブラインドとアンティの構造を返す
+    #[inline]
+    pub fn blinds(&self) -> &BlindStructure {
+        &self.blinds
+    }
+
+    /// オープンレイズの額の一覧を返す
+    #[inline]
+    pub fn raise_sizes(&self) -> &[f64] {
+        &self.raise_sizes
+    }
+
+    /// コール後の各プレイヤーのエクイティ実現率を返す
+    #[inline]
+    pub fn realization(&self) -> [f64; 2] {
+        self.realization
+    }
+
+    /// `player` の初期レンジを設定する (`range` の各要素は手札が実現している確率)
+    pub fn set_initial_range(&mut self, player: usize, range: Vec<f64>) {
+        self.ranges.set(player, range);
+    }
+
+    /// 利得の期待値 `ev` から `player` のハンド終了時のスタックの期待値を返す
+    #[inline]
+    pub fn expected_stack(&self, player: usize, ev: f64) -> f64 {
+        self.stacks[player] + ev
+    }
+
+    /// レイズ後の額が `amount` のとき `player` がポットに入れた額を返す
+    #[inline]
+    fn contribution(&self, player: usize, amount: f64) -> f64 {
+        match player {
+            0 => amount + self.blinds.ante,
+            _ => amount + self.blinds.ante + self.blinds.bb_ante,
+        }
+    }
+
+    /// 終端ノード `node` の種類を返す
+    #[inline]
+    fn terminal(&self, node: &RaiseNode) -> Terminal {
+        let shove = node.shove();
+        match *node.public_history.as_slice() {
+            [FOLD] => Terminal::Fold {
+                player: 0,
+                amount: self.blinds.posted(0),
+            },
+            [_, FOLD] => Terminal::Fold {
+                player: 1,
+                amount: self.blinds.posted(1),
+            },
+            [raise, THREE_BET_SHOVE, FOLD] => Terminal::Fold {
+                player: 0,
+                amount: self.contribution(0, self.raise_sizes[raise - 1]),
+            },
+            [raise, CALL] if raise != shove => Terminal::Called {
+                amount: self.raise_sizes[raise - 1],
+            },
+            _ => Terminal::AllIn,
+        }
+    }
+
+    /// レイズ後の額 `amount` でコールされた後、`player` の勝率が `equity` のときの利得を返す
+    #[inline]
+    fn called_payoff(&self, player: usize, amount: f64, equity: f64) -> f64 {
+        let pot = self.contribution(0, amount) + self.contribution(1, amount);
+        let share = realized_share(self.realization, player, equity);
+        share * pot - self.contribution(player, amount)
+    }
+}
+
+/// "2,2.5" のようなカンマ区切りのオープンレイズの額の一覧を読み取る
+pub fn parse_raise_sizes(s: &str) -> Result<Vec<f64>, String> {
+    let ret = s
+        .split(',')
+        .map(|x| x.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("invalid raise sizes: '{}'", s))?;
+    match ret.is_empty() {
+        true => Err(format!("invalid raise sizes: '{}'", s)),
+        false => Ok(ret),
+    }
+}
+
+impl RaiseNode {
+    /// オールインのアクションの番号 (最初の手番) を返す
+    #[inline]
+    fn shove(&self) -> Action {
+        self.raise_sizes.len() + 1
+    }
+}
+
+impl GameNode for RaiseNode {
+    #[inline]
+    fn public_history(&self) -> &PublicHistory {
+        &self.public_history
+    }
+
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        match *self.public_history.as_slice() {
+            [] | [_, THREE_BET_SHOVE] => false,
+            [action] => action == FOLD,
+            _ => true,
+        }
+    }
+
+    #[inline]
+    fn current_player(&self) -> usize {
+        self.public_history.len() % 2
+    }
+
+    #[inline]
+    fn num_actions(&self) -> usize {
+        match *self.public_history.as_slice() {
+            [] => self.raise_sizes.len() + 2,
+            [raise] if raise != self.shove() => 3,
+            _ => 2,
+        }
+    }
+
+    #[inline]
+    fn action_name(&self, action: Action) -> String {
+        match (self.public_history.as_slice(), action) {
+            (_, FOLD) => "Fold".to_string(),
+            ([], action) if action == self.shove() => "Shove".to_string(),
+            ([], action) => format!("Raise {}", self.raise_sizes[action - 1]),
+            ([_], THREE_BET_SHOVE) => "3-bet Shove".to_string(),
+            _ => "Call".to_string(),
+        }
+    }
+
+    #[inline]
+    fn play(&self, action: usize) -> Self {
+        let mut ret = self.clone();
+        ret.public_history.push(action);
+        ret
+    }
+}
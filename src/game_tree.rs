@@ -0,0 +1,571 @@
+use crate::cards::*;
+use crate::game_limp::realized_share;
+use crate::game_push_fold::*;
+use crate::interface::*;
+use crate::range::InitialRanges;
+use crate::strategy::{parse_parameter, parse_parameter_or};
+use std::sync::Arc;
+
+/// ゲームの名前
+pub const GAME_NAME: &str = "Heads-up Preflop Betting Tree";
+
+/// ヘッズアップのプリフロップのアクションツリーの設定
+///
+/// ベットの額はアンティを除いたもの (各プレイヤーのレイズ後の額) で、単位は bb
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeConfig {
+    /// 各プレイヤーのスタック (ブラインドとアンティを支払う前のもの)
+    pub stacks: [f64; 2],
+
+    /// ブラインドとアンティの構造
+    pub blinds: BlindStructure,
+
+    /// 最初のレイズ (オープンレイズ、リンプに対するレイズ) のレイズ後の額の一覧
+    pub open_sizes: Vec<f64>,
+
+    /// 3ベットのレイズ後の額の一覧 (直前のレイズ後の額に対する倍率)
+    pub three_bet_sizes: Vec<f64>,
+
+    /// 4ベットのレイズ後の額の一覧 (直前のレイズ後の額に対する倍率)
+    pub four_bet_sizes: Vec<f64>,
+
+    /// レイズ後の額がオールインの額に対してこの割合以上となるレイズはオールインに置き換える
+    pub allin_threshold: f64,
+
+    /// 1ハンドで可能なレイズ (オールインを含む) の回数の上限
+    pub max_raises: usize,
+
+    /// スモールブラインドが最初の手番でリンプできるかどうか
+    pub allow_limp: bool,
+
+    /// レイズ可能な全ての手番でオールインを選べるかどうか
+    pub allow_allin: bool,
+
+    /// オールイン以外でショーダウンに進んだ場合の各プレイヤーのエクイティ実現率
+    pub realization: [f64; 2],
+}
+
+impl TreeConfig {
+    /// スモールブラインドがフォールドかオールインのみを選べる設定 (`PushFoldGame` と同じツリー)
+    pub fn push_fold(stacks: [f64; 2], blinds: BlindStructure) -> Self {
+        Self {
+            stacks,
+            blinds,
+            open_sizes: Vec::new(),
+            three_bet_sizes: Vec::new(),
+            four_bet_sizes: Vec::new(),
+            allin_threshold: 1.0,
+            max_raises: 1,
+            allow_limp: false,
+            allow_allin: true,
+            realization: [1.0, 1.0],
+        }
+    }
+
+    /// スモールブラインドがリンプも選べる設定 (`LimpGame` と同じツリー)
+    pub fn limp(stacks: [f64; 2], blinds: BlindStructure, realization: [f64; 2]) -> Self {
+        Self {
+            max_raises: 2,
+            allow_limp: true,
+            realization,
+            ..Self::push_fold(stacks, blinds)
+        }
+    }
+
+    /// オープンレイズに対して3ベットオールインを選べる設定 (`RaiseGame` と同じツリー)
+    pub fn raise(
+        stacks: [f64; 2],
+        blinds: BlindStructure,
+        open_sizes: Vec<f64>,
+        realization: [f64; 2],
+    ) -> Self {
+        Self {
+            open_sizes,
+            max_raises: 2,
+            realization,
+            ..Self::push_fold(stacks, blinds)
+        }
+    }
+
+    /// 設定が妥当かどうかを検査する
+    pub fn validate(&self) -> Result<(), String> {
+        let blinds = &self.blinds;
+        if !(0.0 <= blinds.small_blind && blinds.small_blind <= blinds.big_blind) {
+            return Err("small blind must be between 0 and the big blind".to_string());
+        }
+        if blinds.ante < 0.0 || blinds.bb_ante < 0.0 {
+            return Err("antes must not be negative".to_string());
+        }
+        if blinds.posted(1) > self.stacks[0].min(self.stacks[1]) {
+            return Err("effective stack must cover the blinds and antes".to_string());
+        }
+        if self.open_sizes.iter().any(|&size| size <= blinds.big_blind) {
+            return Err("open sizes must be greater than the big blind".to_string());
+        }
+        if self
+            .three_bet_sizes
+            .iter()
+            .chain(&self.four_bet_sizes)
+            .any(|&size| size <= 1.0)
+        {
+            return Err("3-bet and 4-bet sizes must be greater than 1".to_string());
+        }
+        if !(self.allin_threshold > 0.0 && self.allin_threshold <= 1.0) {
+            return Err("all-in threshold must be in (0, 1]".to_string());
+        }
+        if !self.allow_limp
+            && (self.max_raises == 0 || (self.open_sizes.is_empty() && !self.allow_allin))
+        {
+            return Err("small blind must have an action other than fold".to_string());
+        }
+        if self.realization.iter().any(|&r| r <= 0.0) {
+            return Err("equity realization must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// `level` 回目のレイズのレイズ後の額の候補を返す (`facing` は直前のベットの額)
+    fn raise_sizes(&self, level: usize, facing: f64) -> Vec<f64> {
+        match level {
+            1 => self.open_sizes.clone(),
+            2 => self.three_bet_sizes.iter().map(|x| x * facing).collect(),
+            3 => self.four_bet_sizes.iter().map(|x| x * facing).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// 設定から構築したヘッズアップのプリフロップ
+///
+/// オールインのショーダウンでは勝率テーブルの勝率でポットを分け合い、
+/// それ以外でショーダウンに進んだ場合は勝率に各プレイヤーのエクイティ実現率を掛けた値の比で
+/// ポットを分け合うものとみなす
+pub struct TreeGame {
+    config: TreeConfig,
+    tree: Arc<Vec<NodeData>>,
+    ranges: InitialRanges,
+}
+
+#[derive(Clone)]
+pub struct TreeNode {
+    public_history: PublicHistory,
+    tree: Arc<Vec<NodeData>>,
+    index: usize,
+}
+
+/// ツリーの各ノードの内容
+enum NodeData {
+    /// `player` の手番 (各アクションの名前と遷移先のノードの番号)
+    Decision {
+        player: usize,
+        actions: Vec<(String, usize)>,
+    },
+
+    /// `folder` がフォールドした (`contributions` は各プレイヤーがポットに入れた額)
+    Fold {
+        folder: usize,
+        contributions: [f64; 2],
+    },
+
+    /// ショーダウン (`all_in` が偽の場合はエクイティ実現率を用いる)
+    Showdown {
+        contributions: [f64; 2],
+        all_in: bool,
+    },
+}
+
+/// ツリーの構築中の状態
+struct Builder<'a> {
+    config: &'a TreeConfig,
+    cap: f64,
+    nodes: Vec<NodeData>,
+}
+
+impl Builder<'_> {
+    /// 各プレイヤーのベットが `bets` の時点でポットに入れた額を返す
+    fn contributions(&self, bets: [f64; 2]) -> [f64; 2] {
+        let blinds = &self.config.blinds;
+        [bets[0] + blinds.dead(0), bets[1] + blinds.dead(1)]
+    }
+
+    /// ノードを追加してその番号を返す
+    fn push(&mut self, data: NodeData) -> usize {
+        self.nodes.push(data);
+        self.nodes.len() - 1
+    }
+
+    /// `player` の手番のノードとその子孫を構築し、そのノードの番号を返す
+    ///
+    /// `num_raises` はそれまでのレイズの回数で、`is_root` は最初の手番かどうか
+    fn build(&mut self, bets: [f64; 2], player: usize, num_raises: usize, is_root: bool) -> usize {
+        let index = self.push(NodeData::Decision {
+            player,
+            actions: Vec::new(),
+        });
+        let opponent = player ^ 1;
+        let facing = bets[opponent];
+        let mut actions = Vec::new();
+
+        // フォールド
+        if bets[player] < facing {
+            let contributions = self.contributions(bets);
+            let child = self.push(NodeData::Fold {
+                folder: player,
+                contributions,
+            });
+            actions.push(("Fold".to_string(), child));
+        }
+
+        // コール・チェック・リンプ
+        let mut called = bets;
+        called[player] = facing;
+        if is_root {
+            if self.config.allow_limp {
+                let child = self.build(called, opponent, num_raises, false);
+                actions.push(("Limp".to_string(), child));
+            }
+        } else {
+            let name = match bets[player] < facing {
+                true => "Call",
+                false => "Check",
+            };
+            let child = self.push(NodeData::Showdown {
+                contributions: self.contributions(called),
+                all_in: facing >= self.cap,
+            });
+            actions.push((name.to_string(), child));
+        }
+
+        // レイズ・オールイン
+        if num_raises < self.config.max_raises && facing < self.cap {
+            let mut sizes = Vec::new();
+            let mut all_in = self.config.allow_allin;
+            for size in self.config.raise_sizes(num_raises + 1, facing) {
+                if size >= self.config.allin_threshold * self.cap {
+                    all_in = true;
+                } else if size > facing && !sizes.contains(&size) {
+                    sizes.push(size);
+                }
+            }
+            for size in sizes {
+                let mut raised = bets;
+                raised[player] = size;
+                let child = self.build(raised, opponent, num_raises + 1, false);
+                actions.push((format!("Raise {}", size), child));
+            }
+            if all_in {
+                let mut raised = bets;
+                raised[player] = self.cap;
+                let child = self.build(raised, opponent, num_raises + 1, false);
+                actions.push(("All-in".to_string(), child));
+            }
+        }
+
+        self.nodes[index] = NodeData::Decision { player, actions };
+        index
+    }
+}
+
+impl Game for TreeGame {
+    type Node = TreeNode;
+
+    #[inline]
+    fn name(&self) -> String {
+        GAME_NAME.to_string()
+    }
+
+    #[inline]
+    fn parameters(&self) -> Vec<(String, String)> {
+        let config = &self.config;
+        let mut ret = vec![
+            ("sb_stack".to_string(), config.stacks[0].to_string()),
+            ("bb_stack".to_string(), config.stacks[1].to_string()),
+        ];
+        ret.extend(config.blinds.parameters());
+        ret.extend([
+            (
+                "open_sizes".to_string(),
+                format_sizes(&config.open_sizes, ","),
+            ),
+            (
+                "three_bet_sizes".to_string(),
+                format_sizes(&config.three_bet_sizes, ","),
+            ),
+            (
+                "four_bet_sizes".to_string(),
+                format_sizes(&config.four_bet_sizes, ","),
+            ),
+            (
+                "allin_threshold".to_string(),
+                config.allin_threshold.to_string(),
+            ),
+            ("max_raises".to_string(), config.max_raises.to_string()),
+            ("allow_limp".to_string(), config.allow_limp.to_string()),
+            ("allow_allin".to_string(), config.allow_allin.to_string()),
+            (
+                "sb_realization".to_string(),
+                config.realization[0].to_string(),
+            ),
+            (
+                "bb_realization".to_string(),
+                config.realization[1].to_string(),
+            ),
+        ]);
+        ret.extend(self.ranges.parameters());
+        ret
+    }
+
+    #[inline]
+    fn root(&self) -> TreeNode {
+        TreeNode {
+            public_history: Vec::new(),
+            tree: Arc::clone(&self.tree),
+            index: 0,
+        }
+    }
+
+    #[inline]
+    fn num_private_hands(&self) -> usize {
+        NUM_COMBOS
+    }
+
+    #[inline]
+    fn initial_reach(&self, player: usize) -> Vec<f64> {
+        self.ranges.get(player).to_vec()
+    }
+
+    #[inline]
+    fn hand_name(&self, hand: usize) -> String {
+        combo_to_string(hand)
+    }
+
+    #[inline]
+    fn chance_reach(&self, pmi: &[f64]) -> Vec<f64> {
+        holdem_chance_reach(Deck::Standard, pmi)
+    }
+
+    #[inline]
+    fn evaluate(&self, node: &TreeNode, player: usize, pmi: &Vec<f64>) -> Vec<f64> {
+        match *node.data() {
+            NodeData::Fold {
+                folder,
+                contributions,
+            } => {
+                let payoff = fold_payoff(folder, contributions, player);
+                let mut ret = self.chance_reach(pmi);
+                ret.iter_mut().for_each(|el| *el *= payoff);
+                ret
+            }
+            NodeData::Showdown {
+                contributions,
+                all_in,
+            } => showdown_cfvalues(Deck::Standard, pmi, |equity| {
+                self.showdown_payoff(contributions, all_in, player, equity)
+            }),
+            NodeData::Decision { .. } => unreachable!(),
+        }
+    }
+
+    #[inline]
+    fn is_valid_deal(&self, hands: [usize; 2]) -> bool {
+        combos_disjoint(hands[0], hands[1])
+    }
+
+    #[inline]
+    fn evaluate_hands(&self, node: &TreeNode, player: usize, hands: [usize; 2]) -> f64 {
+        match *node.data() {
+            NodeData::Fold {
+                folder,
+                contributions,
+            } => fold_payoff(folder, contributions, player),
+            NodeData::Showdown {
+                contributions,
+                all_in,
+            } => {
+                let equity = equity(Deck::Standard, [hands[player], hands[player ^ 1]]);
+                self.showdown_payoff(contributions, all_in, player, equity)
+            }
+            NodeData::Decision { .. } => unreachable!(),
+        }
+    }
+}
+
+impl TreeGame {
+    /// 設定 `config` からアクションツリーを構築する
+    ///
+    /// 設定が妥当でない場合はパニックする (事前に `TreeConfig::validate()` で検査できる)
+    pub fn new(config: TreeConfig) -> Self {
+        if let Err(e) = config.validate() {
+            panic!("{}", e);
+        }
+
+        // オールインの額 (アンティを除いたスタックの小さい方)
+        let blinds = &config.blinds;
+        let cap = (config.stacks[0] - blinds.dead(0)).min(config.stacks[1] - blinds.dead(1));
+
+        let mut builder = Builder {
+            config: &config,
+            cap,
+            nodes: Vec::new(),
+        };
+        builder.build([blinds.small_blind, blinds.big_blind], 0, 0, true);
+        let tree = Arc::new(builder.nodes);
+
+        Self {
+            config,
+            tree,
+            ranges: InitialRanges::default(),
+        }
+    }
+
+    /// `Game::parameters()` が返すパラメータの列からゲームを復元する
+    pub fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        let sizes = |name: &str| parse_sizes(&parse_parameter::<String>(parameters, name)?);
+        let config = TreeConfig {
+            stacks: [
+                parse_parameter(parameters, "sb_stack")?,
+                parse_parameter(parameters, "bb_stack")?,
+            ],
+            blinds: BlindStructure::from_parameters(parameters)?,
+            open_sizes: sizes("open_sizes")?,
+            three_bet_sizes: sizes("three_bet_sizes")?,
+            four_bet_sizes: sizes("four_bet_sizes")?,
+            allin_threshold: parse_parameter(parameters, "allin_threshold")?,
+            max_raises: parse_parameter(parameters, "max_raises")?,
+            allow_limp: parse_parameter(parameters, "allow_limp")?,
+            allow_allin: parse_parameter(parameters, "allow_allin")?,
+            realization: [
+                parse_parameter_or(parameters, "sb_realization", 1.0)?,
+                parse_parameter_or(parameters, "bb_realization", 1.0)?,
+            ],
+        };
+        config.validate()?;
+        let mut ret = Self::new(config);
+        ret.ranges = InitialRanges::from_parameters(parameters)?;
+        Ok(ret)
+    }
+
+    /// ツリーの設定を返す
+    #[inline]
+    pub fn config(&self) -> &TreeConfig {
+        &self.config
+    }
+
+    /// `player` の初期レンジを設定する (`range` の各要素は手札が実現している確率)
+    pub fn set_initial_range(&mut self, player: usize, range: Vec<f64>) {
+        self.ranges.set(player, range);
+    }
+
+    /// ツリーのノード数 (終端ノードを含む) を返す
+    #[inline]
+    pub fn num_nodes(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// 利得の期待値 `ev` から `player` のハンド終了時のスタックの期待値を返す
+    #[inline]
+    pub fn expected_stack(&self, player: usize, ev: f64) -> f64 {
+        self.config.stacks[player] + ev
+    }
+
+    /// 各プレイヤーがポットに `contributions` を入れたショーダウンにおいて、
+    /// 勝率が `equity` の `player` の利得を返す
+    #[inline]
+    fn showdown_payoff(
+        &self,
+        contributions: [f64; 2],
+        all_in: bool,
+        player: usize,
+        equity: f64,
+    ) -> f64 {
+        let share = match all_in {
+            true => equity,
+            false => realized_share(self.config.realization, player, equity),
+        };
+        share * (contributions[0] + contributions[1]) - contributions[player]
+    }
+}
+
+/// `folder` がフォールドしたときの `player` の利得を返す (フォールドしたプレイヤーはポットに入れた額を失う)
+#[inline]
+fn fold_payoff(folder: usize, contributions: [f64; 2], player: usize) -> f64 {
+    match player == folder {
+        true => -contributions[folder],
+        false => contributions[folder],
+    }
+}
+
+/// "2,2.5" のようなカンマ区切りの額の一覧を読み取る (空文字列は空の一覧とする)
+pub fn parse_sizes(s: &str) -> Result<Vec<f64>, String> {
+    if s.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    s.split(',')
+        .map(|x| x.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("invalid sizes: '{}'", s))
+}
+
+/// 額の一覧を区切り文字 `separator` で連結した文字列にする
+pub fn format_sizes(sizes: &[f64], separator: &str) -> String {
+    sizes
+        .iter()
+        .map(|size| size.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+impl TreeNode {
+    /// ノードの内容を返す
+    #[inline]
+    fn data(&self) -> &NodeData {
+        &self.tree[self.index]
+    }
+
+    /// 手番のノードのアクションの一覧を返す
+    #[inline]
+    fn decision_actions(&self) -> &[(String, usize)] {
+        match self.data() {
+            NodeData::Decision { actions, .. } => actions,
+            _ => &[],
+        }
+    }
+}
+
+impl GameNode for TreeNode {
+    #[inline]
+    fn public_history(&self) -> &PublicHistory {
+        &self.public_history
+    }
+
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        !matches!(self.data(), NodeData::Decision { .. })
+    }
+
+    #[inline]
+    fn current_player(&self) -> usize {
+        match *self.data() {
+            NodeData::Decision { player, .. } => player,
+            _ => self.public_history.len() % 2,
+        }
+    }
+
+    #[inline]
+    fn num_actions(&self) -> usize {
+        self.decision_actions().len()
+    }
+
+    #[inline]
+    fn action_name(&self, action: Action) -> String {
+        self.decision_actions()[action].0.clone()
+    }
+
+    #[inline]
+    fn play(&self, action: usize) -> Self {
+        let mut ret = self.clone();
+        ret.public_history.push(action);
+        ret.index = self.decision_actions()[action].1;
+        ret
+    }
+}
@@ -0,0 +1,351 @@
+use crate::cards::*;
+
+/// ランクを表す文字 (強い順、グリッドの行・列の見出し)
+const GRID_RANKS: [char; 13] = [
+    'A', 'K', 'Q', 'J', 'T', '9', '8', '7', '6', '5', '4', '3', '2',
+];
+
+/// グリッドの各セルの値の書式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellFormat {
+    /// 0以上1以下の値を百分率で表示する (戦略の確率など)
+    Percent,
+
+    /// 符号付きの値を表示する (期待値や差分など)
+    Signed,
+
+    /// -1以上1以下の符号付きの値を百分率で表示する (確率の差分など)
+    PercentDiff,
+
+    /// 符号なしの値を表示する (スタックの閾値など)
+    Number,
+}
+
+impl CellFormat {
+    /// 値 `value` を幅6の文字列に変換する
+    pub fn format(&self, value: f64) -> String {
+        if value.is_nan() {
+            return "      ".to_string();
+        }
+        match self {
+            CellFormat::Percent if value >= 0.9995 => " 100.%".to_string(),
+            CellFormat::Percent if value < 0.0005 => "   -  ".to_string(),
+            CellFormat::Percent => format!(" {:>4.1}%", 100.0 * value),
+            CellFormat::Signed if value.abs() < 0.005 => "   -  ".to_string(),
+            CellFormat::Signed => format!(" {:>+5.2}", value),
+            CellFormat::PercentDiff if value.abs() < 0.0005 => "   -  ".to_string(),
+            CellFormat::PercentDiff if value >= 0.9995 => "+100.%".to_string(),
+            CellFormat::PercentDiff if value <= -0.9995 => "-100.%".to_string(),
+            CellFormat::PercentDiff => format!("{:>+5.1}%", 100.0 * value),
+            CellFormat::Number => format!(" {:>5.1}", value),
+        }
+    }
+}
+
+/// 13x13 のハンドグリッド (左上から右下への対角線がペア、右上がスーテッド、左下がオフスート)
+#[derive(Clone, Debug)]
+pub struct HandGrid {
+    /// 各セルの値 (`cells[row][col]`、行・列の0番目が "A")
+    cells: [[f64; 13]; 13],
+
+    /// 各セルに集約された重みの和
+    weights: [[f64; 13]; 13],
+}
+
+impl HandGrid {
+    /// グリッドの `(row, col)` に対応するハンドクラス `(高いランク, 低いランク, スーテッドか)` を返す
+    #[inline]
+    pub fn cell_class(row: usize, col: usize) -> (usize, usize, bool) {
+        let (rank1, rank2) = (12 - row, 12 - col);
+        (rank1.max(rank2), rank1.min(rank2), row < col)
+    }
+
+    /// 手札 `index` が属するグリッドのセル `(row, col)` を返す
+    #[inline]
+    pub fn cell_of(index: usize) -> (usize, usize) {
+        let (high, low, suited) = hand_class(index);
+        if suited {
+            (12 - high, 12 - low)
+        } else {
+            (12 - low, 12 - high)
+        }
+    }
+
+    /// グリッドの `(row, col)` に対応するハンドクラスの名前 ("AKs" など) を返す
+    #[inline]
+    pub fn class_name(row: usize, col: usize) -> String {
+        let (high, low, suited) = Self::cell_class(row, col);
+        hand_class_to_string(high, low, suited)
+    }
+
+    /// グリッドの `(row, col)` に属する手札の個数を返す
+    #[inline]
+    pub fn combo_count(row: usize, col: usize) -> usize {
+        match row.cmp(&col) {
+            std::cmp::Ordering::Equal => 6,
+            std::cmp::Ordering::Less => 4,
+            std::cmp::Ordering::Greater => 12,
+        }
+    }
+
+    /// 手札毎の値 `values` を、各セルでの単純平均によって集約する
+    pub fn from_combos(values: &[f64]) -> Self {
+        Self::from_combos_weighted(values, &vec![1.0; NUM_COMBOS])
+    }
+
+    /// 手札毎の値 `values` を、各セルでの `weights` による加重平均によって集約する
+    ///
+    /// 重みの和が0のセルの値は NaN となる
+    pub fn from_combos_weighted(values: &[f64], weights: &[f64]) -> Self {
+        assert_eq!(values.len(), NUM_COMBOS);
+        assert_eq!(weights.len(), NUM_COMBOS);
+
+        let mut cells = [[0.0; 13]; 13];
+        let mut cell_weights = [[0.0; 13]; 13];
+        for index in 0..NUM_COMBOS {
+            let (row, col) = Self::cell_of(index);
+            cells[row][col] += weights[index] * values[index];
+            cell_weights[row][col] += weights[index];
+        }
+
+        for row in 0..13 {
+            for col in 0..13 {
+                cells[row][col] = if cell_weights[row][col] == 0.0 {
+                    f64::NAN
+                } else {
+                    cells[row][col] / cell_weights[row][col]
+                };
+            }
+        }
+
+        Self {
+            cells,
+            weights: cell_weights,
+        }
+    }
+
+    /// セルの値を直接与えてグリッドを作る (重みは手札の個数とする)
+    pub fn from_cells(cells: [[f64; 13]; 13]) -> Self {
+        let mut weights = [[0.0; 13]; 13];
+        for (row, weights_row) in weights.iter_mut().enumerate() {
+            for (col, weight) in weights_row.iter_mut().enumerate() {
+                *weight = Self::combo_count(row, col) as f64;
+            }
+        }
+        Self { cells, weights }
+    }
+
+    /// セル `(row, col)` の値を返す
+    #[inline]
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.cells[row][col]
+    }
+
+    /// 全ての手札にわたる (集約時の重みによる) 加重平均を返す
+    pub fn overall(&self) -> f64 {
+        let mut sum = 0.0;
+        let mut weight_sum = 0.0;
+        for row in 0..13 {
+            for col in 0..13 {
+                if self.weights[row][col] > 0.0 {
+                    sum += self.weights[row][col] * self.cells[row][col];
+                    weight_sum += self.weights[row][col];
+                }
+            }
+        }
+        sum / weight_sum
+    }
+
+    /// 色付けに用いる値の範囲を返す
+    fn color_range(&self, format: CellFormat) -> (f64, f64) {
+        let finite = self.cells.iter().flatten().filter(|v| v.is_finite());
+        match format {
+            CellFormat::Percent => (0.0, 1.0),
+            CellFormat::PercentDiff => (-1.0, 1.0),
+            CellFormat::Signed => {
+                let max_abs = finite.fold(0.0, |acc: f64, v| acc.max(v.abs()));
+                (-max_abs, max_abs)
+            }
+            CellFormat::Number => finite.fold((f64::INFINITY, f64::NEG_INFINITY), |acc, &v| {
+                (acc.0.min(v), acc.1.max(v))
+            }),
+        }
+    }
+
+    /// セル `(row, col)` の背景色を返す
+    fn cell_color(&self, row: usize, col: usize, format: CellFormat) -> (u8, u8, u8) {
+        const BASE: (f64, f64, f64) = (245.0, 245.0, 245.0);
+        const HIGH: (f64, f64, f64) = (214.0, 39.0, 40.0);
+        const LOW: (f64, f64, f64) = (31.0, 119.0, 180.0);
+
+        let value = self.cells[row][col];
+        let (lo, hi) = self.color_range(format);
+        if !value.is_finite() || hi <= lo {
+            return (BASE.0 as u8, BASE.1 as u8, BASE.2 as u8);
+        }
+
+        let (target, t) = match format {
+            CellFormat::Signed | CellFormat::PercentDiff if value < 0.0 => (LOW, value / lo),
+            CellFormat::Signed | CellFormat::PercentDiff => (HIGH, value / hi),
+            _ => (HIGH, (value - lo) / (hi - lo)),
+        };
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |a: f64, b: f64| (a + (b - a) * t).round() as u8;
+        (
+            lerp(BASE.0, target.0),
+            lerp(BASE.1, target.1),
+            lerp(BASE.2, target.2),
+        )
+    }
+
+    /// ASCII 文字のみからなる表として描画する
+    pub fn to_ascii(&self, format: CellFormat) -> String {
+        let mut ret = String::new();
+        ret.push_str(
+            " |   A     K     Q     J     T     9     8     7     6     5     4     3     2\n",
+        );
+        ret.push_str(
+            "-+------------------------------------------------------------------------------\n",
+        );
+        for (row, rank) in GRID_RANKS.iter().enumerate() {
+            ret.push(*rank);
+            ret.push('|');
+            for col in 0..13 {
+                ret.push_str(&format.format(self.cells[row][col]));
+            }
+            ret.push('\n');
+        }
+        ret
+    }
+
+    /// ANSI エスケープシーケンスによって背景色を付けた表として描画する
+    pub fn to_ansi(&self, format: CellFormat) -> String {
+        let mut ret = String::new();
+        ret.push_str("   ");
+        for rank in GRID_RANKS {
+            ret.push_str(&format!("   {}  ", rank));
+        }
+        ret.push('\n');
+        for (row, rank) in GRID_RANKS.iter().enumerate() {
+            ret.push_str(&format!(" {} ", rank));
+            for col in 0..13 {
+                let (r, g, b) = self.cell_color(row, col, format);
+                ret.push_str(&format!(
+                    "\x1b[48;2;{};{};{}m\x1b[38;2;0;0;0m{}\x1b[0m",
+                    r,
+                    g,
+                    b,
+                    format.format(self.cells[row][col])
+                ));
+            }
+            ret.push('\n');
+        }
+        ret
+    }
+
+    /// HTML の `<table>` 要素として描画する
+    pub fn to_html(&self, title: &str, format: CellFormat) -> String {
+        let mut ret = String::new();
+        ret.push_str("<table style=\"border-collapse: collapse; font-family: monospace;\">\n");
+        ret.push_str(&format!("  <caption>{}</caption>\n", escape_xml(title)));
+        for row in 0..13 {
+            ret.push_str("  <tr>\n");
+            for col in 0..13 {
+                let (r, g, b) = self.cell_color(row, col, format);
+                ret.push_str(&format!(
+                    "    <td title=\"{} ({} combos)\" style=\"background: rgb({}, {}, {}); \
+                     border: 1px solid #ccc; padding: 2px 4px; text-align: center;\">\
+                     <b>{}</b><br>{}</td>\n",
+                    Self::class_name(row, col),
+                    Self::combo_count(row, col),
+                    r,
+                    g,
+                    b,
+                    Self::class_name(row, col),
+                    format.format(self.cells[row][col]).trim()
+                ));
+            }
+            ret.push_str("  </tr>\n");
+        }
+        ret.push_str("</table>\n");
+        ret
+    }
+
+    /// 単体で表示可能な SVG 画像として描画する
+    pub fn to_svg(&self, title: &str, format: CellFormat) -> String {
+        grids_to_svg(&[(title, self)], format)
+    }
+
+    /// SVG 画像の要素を `(x_offset, 0)` を左上として描画する
+    fn svg_elements(&self, title: &str, format: CellFormat, x_offset: usize) -> String {
+        let mut ret = String::new();
+        ret.push_str(&format!(
+            "  <text x=\"{}\" y=\"{}\" font-size=\"16\">{}</text>\n",
+            x_offset + SVG_GRID_SIZE / 2,
+            SVG_HEADER - 10,
+            escape_xml(title)
+        ));
+        for row in 0..13 {
+            for col in 0..13 {
+                let (r, g, b) = self.cell_color(row, col, format);
+                let (x, y) = (x_offset + col * SVG_CELL, SVG_HEADER + row * SVG_CELL);
+                ret.push_str(&format!(
+                    "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" \
+                     fill=\"rgb({},{},{})\" stroke=\"#ccc\"/>\n",
+                    x, y, SVG_CELL, SVG_CELL, r, g, b
+                ));
+                ret.push_str(&format!(
+                    "  <text x=\"{}\" y=\"{}\" font-size=\"12\" font-weight=\"bold\">{}</text>\n",
+                    x + SVG_CELL / 2,
+                    y + 20,
+                    Self::class_name(row, col)
+                ));
+                ret.push_str(&format!(
+                    "  <text x=\"{}\" y=\"{}\" font-size=\"11\">{}</text>\n",
+                    x + SVG_CELL / 2,
+                    y + 36,
+                    format.format(self.cells[row][col]).trim()
+                ));
+            }
+        }
+        ret
+    }
+}
+
+/// SVG 画像におけるセルの大きさ
+const SVG_CELL: usize = 48;
+
+/// SVG 画像におけるグリッドの大きさ
+const SVG_GRID_SIZE: usize = 13 * SVG_CELL;
+
+/// SVG 画像における見出しの高さ
+const SVG_HEADER: usize = 32;
+
+/// SVG 画像におけるグリッド間の余白
+const SVG_MARGIN: usize = 24;
+
+/// 複数のグリッドを横に並べ、単体で表示可能な SVG 画像として描画する
+pub fn grids_to_svg(grids: &[(&str, &HandGrid)], format: CellFormat) -> String {
+    let width = grids.len() * (SVG_GRID_SIZE + SVG_MARGIN) - SVG_MARGIN;
+    let mut ret = String::new();
+    ret.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+         font-family=\"monospace\" text-anchor=\"middle\">\n",
+        width,
+        SVG_GRID_SIZE + SVG_HEADER
+    ));
+    for (index, (title, grid)) in grids.iter().enumerate() {
+        let x_offset = index * (SVG_GRID_SIZE + SVG_MARGIN);
+        ret.push_str(&grid.svg_elements(title, format, x_offset));
+    }
+    ret.push_str("</svg>\n");
+    ret
+}
+
+/// XML の特殊文字をエスケープする
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,309 @@
+use crate::game_tree::{format_sizes, parse_sizes};
+use crate::rng::Rng;
+use crate::strategy::parse_parameter;
+
+/// `tournament_equities()` が厳密な計算を行うプレイヤー数 (スタックが正のもの) の上限
+pub const EXACT_ICM_MAX_PLAYERS: usize = 12;
+
+/// `tournament_equities()` が Monte Carlo 法で近似する際のサンプル数
+pub const ICM_MONTE_CARLO_SAMPLES: usize = 20000;
+
+/// Malmuth-Harville モデルによって各プレイヤーのトーナメントエクイティ (賞金の期待値) を計算する
+///
+/// `payouts` は1位から順の賞金で、プレイヤー数より短い場合は残りを0とみなす。
+/// スタックが0のプレイヤーは残りのプレイヤーより下の順位を等しい確率で分け合う。
+/// 賞金のある順位までしか辿らないため、計算量は O(n Σ_{k<m} C(n, k)) となる
+/// (n はスタックが正のプレイヤー数、m は賞金のある順位の数で、m = n の場合は O(2^n n))
+pub fn icm_equities(stacks: &[f64], payouts: &[f64]) -> Vec<f64> {
+    let alive = (0..stacks.len())
+        .filter(|&p| stacks[p] > 0.0)
+        .collect::<Vec<_>>();
+    let num_alive = alive.len();
+    assert!(num_alive <= 24, "too many players for exact ICM");
+    let num_paid = payouts.len().min(num_alive);
+
+    let mut ret = vec![0.0; stacks.len()];
+
+    // prob[mask]: `mask` のプレイヤーがちょうど上位 |mask| 位を占める確率
+    let total = alive.iter().map(|&p| stacks[p]).sum::<f64>();
+    let mut prob = vec![0.0; 1 << num_alive];
+    let mut mask_sum = vec![0.0; 1 << num_alive];
+    prob[0] = 1.0;
+    for (place, &payout) in payouts[..num_paid].iter().enumerate() {
+        for mask in masks_with_popcount(num_alive, place) {
+            if prob[mask] == 0.0 {
+                continue;
+            }
+            let remaining = total - mask_sum[mask];
+            for (i, &p) in alive.iter().enumerate() {
+                if mask & (1 << i) != 0 {
+                    continue;
+                }
+                let next = mask | (1 << i);
+                let p_next = prob[mask] * stacks[p] / remaining;
+                ret[p] += p_next * payout;
+                prob[next] += p_next;
+                mask_sum[next] = mask_sum[mask] + stacks[p];
+            }
+        }
+    }
+
+    split_busted(stacks, payouts, &mut ret);
+    ret
+}
+
+/// `n` ビットのうちちょうど `k` ビットが立っている整数を昇順に列挙する (`k <= n`)
+fn masks_with_popcount(n: usize, k: usize) -> impl Iterator<Item = usize> {
+    std::iter::successors(Some((1usize << k) - 1), move |&mask| {
+        if mask == 0 {
+            return None;
+        }
+        // 立っているビットの個数を保ったまま次に大きい整数を求める (Gosper's hack)
+        let lowest = mask & mask.wrapping_neg();
+        let ripple = mask + lowest;
+        let next = (((ripple ^ mask) >> 2) / lowest) | ripple;
+        (next < 1 << n).then_some(next)
+    })
+}
+
+/// Malmuth-Harville モデルのトーナメントエクイティを Monte Carlo 法で近似する
+///
+/// 各サンプルでは、スタックに比例する確率で上位から順に順位を決めることと等価な、
+/// スタックを率とする指数分布の乱数の昇順で順位を決める。
+/// 同じ `seed` に対しては同じ乱数列を用いるため、スタックの異なる場合の差が安定する
+pub fn icm_equities_monte_carlo(
+    stacks: &[f64],
+    payouts: &[f64],
+    num_samples: usize,
+    seed: u64,
+) -> Vec<f64> {
+    assert!(num_samples > 0);
+    let alive = (0..stacks.len())
+        .filter(|&p| stacks[p] > 0.0)
+        .collect::<Vec<_>>();
+    let num_paid = payouts.len().min(alive.len());
+
+    let mut ret = vec![0.0; stacks.len()];
+    let mut rng = Rng::new(seed);
+    let mut keys = Vec::with_capacity(alive.len());
+    for _ in 0..num_samples {
+        keys.clear();
+        keys.extend(
+            alive
+                .iter()
+                .map(|&p| (-(1.0 - rng.next_f64()).ln() / stacks[p], p)),
+        );
+        if num_paid < keys.len() {
+            keys.select_nth_unstable_by(num_paid, |a, b| a.0.partial_cmp(&b.0).unwrap());
+        }
+        keys[..num_paid].sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        for (place, &(_, p)) in keys[..num_paid].iter().enumerate() {
+            ret[p] += payouts[place];
+        }
+    }
+    ret.iter_mut().for_each(|el| *el /= num_samples as f64);
+
+    split_busted(stacks, payouts, &mut ret);
+    ret
+}
+
+/// 各プレイヤーのトーナメントエクイティを計算する
+///
+/// スタックが正のプレイヤーが `EXACT_ICM_MAX_PLAYERS` 人以下であれば `icm_equities()` で
+/// 厳密に計算し、それより多ければ `icm_equities_monte_carlo()` で近似する
+pub fn tournament_equities(stacks: &[f64], payouts: &[f64]) -> Vec<f64> {
+    let num_alive = stacks.iter().filter(|&&s| s > 0.0).count();
+    match num_alive <= EXACT_ICM_MAX_PLAYERS {
+        true => icm_equities(stacks, payouts),
+        false => icm_equities_monte_carlo(stacks, payouts, ICM_MONTE_CARLO_SAMPLES, 0),
+    }
+}
+
+/// プレイヤー `i` がプレイヤー `j` とオールインで対決するときのバブルファクターを返す
+///
+/// 2人のうち小さい方のスタックを賭けたとき、負けた場合に失うトーナメントエクイティと
+/// 勝った場合に得るトーナメントエクイティの比である。同額を賭け合う対決で損益分岐となる勝率は
+/// `bf / (1 + bf)` となる。勝っても得るものがない場合は無限大を返す
+pub fn bubble_factor(stacks: &[f64], payouts: &[f64], i: usize, j: usize) -> f64 {
+    assert!(i != j, "bubble factor needs two distinct players");
+    let risk = stacks[i].min(stacks[j]);
+    let current = tournament_equities(stacks, payouts)[i];
+
+    let mut after = stacks.to_vec();
+    after[i] += risk;
+    after[j] -= risk;
+    let win = tournament_equities(&after, payouts)[i];
+    after[i] -= 2.0 * risk;
+    after[j] += 2.0 * risk;
+    let lose = tournament_equities(&after, payouts)[i];
+
+    match win > current {
+        true => (current - lose) / (win - current),
+        false => f64::INFINITY,
+    }
+}
+
+/// 全てのプレイヤーの組 `(i, j)` について `bubble_factor(stacks, payouts, i, j)` を並べた
+/// 行列を返す (対角成分は1とする)
+pub fn bubble_factors(stacks: &[f64], payouts: &[f64]) -> Vec<Vec<f64>> {
+    let n = stacks.len();
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| match i == j {
+                    true => 1.0,
+                    false => bubble_factor(stacks, payouts, i, j),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// スタックが0のプレイヤーに、残りのプレイヤーより下の順位の賞金を等分する
+fn split_busted(stacks: &[f64], payouts: &[f64], equities: &mut [f64]) {
+    let num_alive = stacks.iter().filter(|&&s| s > 0.0).count();
+    let num_busted = stacks.len() - num_alive;
+    if num_busted > 0 {
+        let payout = |place: usize| payouts.get(place).copied().unwrap_or(0.0);
+        let share = (num_alive..stacks.len()).map(payout).sum::<f64>() / num_busted as f64;
+        for (p, &stack) in stacks.iter().enumerate() {
+            if stack <= 0.0 {
+                equities[p] = share;
+            }
+        }
+    }
+}
+
+/// ICM によってチップの増減をトーナメントエクイティの増減に変換するための設定
+#[derive(Clone, Debug, PartialEq)]
+pub struct IcmConfig {
+    /// 1位から順の賞金
+    pub payouts: Vec<f64>,
+
+    /// ハンドに参加していない (同じテーブルまたは他のテーブルに残っている) プレイヤーのスタック
+    pub other_stacks: Vec<f64>,
+}
+
+impl IcmConfig {
+    /// ハンドに参加しているプレイヤーのスタックが `stacks` のときの、
+    /// それらのプレイヤーのトーナメントエクイティを返す
+    pub fn equities(&self, stacks: &[f64]) -> Vec<f64> {
+        let mut all_stacks = stacks.to_vec();
+        all_stacks.extend(&self.other_stacks);
+        let mut ret = tournament_equities(&all_stacks, &self.payouts);
+        ret.truncate(stacks.len());
+        ret
+    }
+
+    /// ハンド開始時のスタックが `stacks` で、ハンドに参加しているプレイヤーのチップの増減が
+    /// `chips` のときの `player` のトーナメントエクイティの増減を返す
+    pub fn utility(&self, stacks: &[f64], chips: &[f64], player: usize) -> f64 {
+        let after = stacks
+            .iter()
+            .zip(chips)
+            .map(|(s, c)| s + c)
+            .collect::<Vec<_>>();
+        self.equities(&after)[player] - self.equities(stacks)[player]
+    }
+
+    /// `Game::parameters()` に含めるパラメータの列を返す
+    pub fn parameters(&self) -> Vec<(String, String)> {
+        vec![
+            ("payouts".to_string(), format_sizes(&self.payouts, ",")),
+            (
+                "other_stacks".to_string(),
+                format_sizes(&self.other_stacks, ","),
+            ),
+        ]
+    }
+
+    /// パラメータの列から設定を復元する (`payouts` が存在しない場合は `None` を返す)
+    pub fn from_parameters(parameters: &[(String, String)]) -> Result<Option<Self>, String> {
+        if !parameters.iter().any(|(k, _)| k == "payouts") {
+            return Ok(None);
+        }
+        let config = Self {
+            payouts: parse_sizes(&parse_parameter::<String>(parameters, "payouts")?)?,
+            other_stacks: parse_sizes(&parse_parameter::<String>(parameters, "other_stacks")?)?,
+        };
+        config.validate()?;
+        Ok(Some(config))
+    }
+
+    /// 設定が妥当かどうかを検査する
+    pub fn validate(&self) -> Result<(), String> {
+        if self.payouts.is_empty() || self.payouts.iter().any(|&p| p < 0.0) {
+            return Err("payouts must be a non-empty list of non-negative values".to_string());
+        }
+        if self.other_stacks.iter().any(|&s| s <= 0.0) {
+            return Err("other stacks must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for IcmConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "payouts = {}", format_sizes(&self.payouts, "/"))?;
+        if !self.other_stacks.is_empty() {
+            write!(
+                f,
+                ", other stacks = {}[bb]",
+                format_sizes(&self.other_stacks, "/")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// スタックが 5000/3000/2000、賞金が 50/30/20 の場合の手計算による値
+    ///
+    /// 例えば 1人目が2位となる確率は 0.3 * 5/7 + 0.2 * 5/8 = 19/56 である
+    #[test]
+    fn three_player_icm() {
+        let equities = icm_equities(&[5000.0, 3000.0, 2000.0], &[50.0, 30.0, 20.0]);
+        let expected = [1075.0 / 28.0, 131.0 / 4.0, 202.0 / 7.0];
+        for (e, x) in equities.iter().zip(expected) {
+            assert!((e - x).abs() < 1e-9, "{} != {}", e, x);
+        }
+    }
+
+    #[test]
+    fn busted_players_share_lower_places() {
+        let equities = icm_equities(&[10.0, 0.0, 0.0], &[50.0, 30.0, 20.0]);
+        assert_eq!(equities, vec![50.0, 25.0, 25.0]);
+    }
+
+    #[test]
+    fn unpaid_places_do_not_change_equities() {
+        // 賞金のない順位を明示的に0として全ての順位を辿った場合と一致する
+        let stacks = [12.0, 7.5, 30.0, 4.0, 18.0, 9.0, 22.0];
+        let paid = icm_equities(&stacks, &[50.0, 30.0, 20.0]);
+        let full = icm_equities(&stacks, &[50.0, 30.0, 20.0, 0.0, 0.0, 0.0, 0.0]);
+        for (a, b) in paid.iter().zip(&full) {
+            assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+        }
+
+        assert_eq!(masks_with_popcount(7, 0).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(masks_with_popcount(7, 3).count(), 35);
+        assert!(masks_with_popcount(7, 3).all(|mask| mask.count_ones() == 3));
+        assert_eq!(masks_with_popcount(7, 7).collect::<Vec<_>>(), vec![127]);
+    }
+
+    #[test]
+    fn monte_carlo_matches_exact() {
+        let stacks = [12.0, 7.5, 30.0, 4.0, 18.0];
+        let payouts = [50.0, 30.0, 20.0];
+        let exact = icm_equities(&stacks, &payouts);
+        let approx = icm_equities_monte_carlo(&stacks, &payouts, 200000, 1);
+        for (e, a) in exact.iter().zip(&approx) {
+            assert!((e - a).abs() < 0.3, "{} != {}", e, a);
+        }
+        let total = approx.iter().sum::<f64>();
+        assert!((total - 100.0).abs() < 1e-9);
+    }
+}
@@ -9,15 +9,56 @@ pub trait Game {
     /// ゲーム木のノードを表す型
     type Node: GameNode;
 
+    /// ゲームの名前を返す
+    fn name(&self) -> String;
+
+    /// ゲームのパラメータを名前と値の組の列として返す
+    fn parameters(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
     /// ゲーム木の根、すなわちゲームの初期履歴を返す
-    fn root() -> Self::Node;
+    fn root(&self) -> Self::Node;
 
     /// プライベートな手札の組み合わせの個数を返す
-    fn num_private_hands() -> usize;
+    fn num_private_hands(&self) -> usize;
+
+    /// ゲームの初期履歴において `player` の各プライベートな手札が実現している確率を返す
+    /// (既定では全ての手札について1、すなわち事前の絞り込みがないものとする)
+    fn initial_reach(&self, _player: usize) -> Vec<f64> {
+        vec![1.0; self.num_private_hands()]
+    }
+
+    /// プライベートな手札 `hand` の名前を返す
+    fn hand_name(&self, hand: usize) -> String {
+        hand.to_string()
+    }
+
+    /// 最初の偶然手番の寄与を含まない相手の到達確率が `pmi` のとき、各手札について
+    /// 最初の偶然手番の寄与を含めた相手の到達確率の和を返す
+    fn chance_reach(&self, pmi: &[f64]) -> Vec<f64>;
 
     /// 終端履歴 `node` において、最初の偶然手番の寄与を含まない counterfactual-到達確率が
     /// `pmi` のときの `player` の counterfactual value を計算する
     fn evaluate(&self, node: &Self::Node, player: usize, pmi: &Vec<f64>) -> Vec<f64>;
+
+    /// プレイヤー0, 1 の手札がそれぞれ `hands[0]`, `hands[1]` となる配り方が可能かどうかを返す
+    fn is_valid_deal(&self, hands: [usize; 2]) -> bool {
+        let mut pmi = vec![0.0; self.num_private_hands()];
+        pmi[hands[1]] = 1.0;
+        self.chance_reach(&pmi)[hands[0]] > 0.0
+    }
+
+    /// 終端履歴 `node` において、プレイヤー0, 1 の手札がそれぞれ `hands[0]`, `hands[1]` のときの
+    /// `player` の利得 (以降の偶然手番についての期待値) を計算する
+    ///
+    /// 既定の実装は `evaluate()` を用いるため低速である
+    fn evaluate_hands(&self, node: &Self::Node, player: usize, hands: [usize; 2]) -> f64 {
+        let mut pmi = vec![0.0; self.num_private_hands()];
+        pmi[hands[player ^ 1]] = 1.0;
+        let cfvalue = self.evaluate(node, player, &pmi)[hands[player]];
+        cfvalue / self.chance_reach(&pmi)[hands[player]]
+    }
 }
 
 /// ゲーム木のノードを表すインターフェース
@@ -39,6 +80,11 @@ pub trait GameNode {
         0..self.num_actions()
     }
 
+    /// アクション `action` の名前を返す
+    fn action_name(&self, action: Action) -> String {
+        action.to_string()
+    }
+
     /// `action` を行った後のノードを返す
     fn play(&self, action: Action) -> Self;
 }
@@ -0,0 +1,21 @@
+pub mod cards;
+pub mod cfr;
+pub mod diff;
+pub mod equity_table;
+pub mod evaluator;
+pub mod export;
+pub mod game_kuhn;
+pub mod game_limp;
+pub mod game_plo;
+pub mod game_push_fold;
+pub mod game_raise;
+pub mod game_tree;
+pub mod grid;
+pub mod icm;
+pub mod interface;
+pub mod range;
+pub mod rng;
+pub mod simulate;
+pub mod strategy;
+pub mod sweep;
+pub mod util;
@@ -0,0 +1,56 @@
+mod commands;
+
+const USAGE: &str = "usage: yabai [--threads <N>] <COMMAND> [ARGS...]
+
+commands:
+    solve       Solve a game with CFR and print or export the result
+    sweep       Solve push/fold over a range of stacks and print the Nash chart
+    eval        Print the EV and exploitability of a saved strategy
+    export      Export a saved strategy as text, JSON, CSV, HTML or SVG
+    diff        Compare two saved strategies
+    match       Simulate a match between two saved strategies
+    gen-table   Generate the preflop equity tables";
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut args = args;
+
+    // `--threads <N>` はサブコマンドより前に指定する
+    if let [flag, value, rest @ ..] = args {
+        if flag == "--threads" {
+            let num_threads = value
+                .parse::<usize>()
+                .map_err(|_| format!("invalid value for '--threads': '{}'", value))?;
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build_global()
+                .map_err(|e| e.to_string())?;
+            args = rest;
+        }
+    }
+
+    let Some((command, rest)) = args.split_first() else {
+        return Err(USAGE.to_string());
+    };
+    match command.as_str() {
+        "solve" => commands::solve::run(rest),
+        "sweep" => commands::sweep::run(rest),
+        "eval" => commands::eval::run(rest),
+        "export" => commands::export::run(rest),
+        "diff" => commands::diff::run(rest),
+        "match" => commands::simulate::run(rest),
+        "gen-table" => commands::gen_table::run(rest),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("unknown command: '{}'\n{}", command, USAGE)),
+    }
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Err(message) = run(&args) {
+        eprintln!("error: {}", message);
+        std::process::exit(1);
+    }
+}
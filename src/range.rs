@@ -0,0 +1,419 @@
+use crate::cards::*;
+use std::collections::BTreeMap;
+
+/// ハンドクラス `(高いランク, 低いランク, スーテッドか)` に属する手札の番号を列挙する
+///
+/// ペアの場合は `suited` は無視される
+pub fn class_combos(high: usize, low: usize, suited: bool) -> Vec<usize> {
+    let mut ret = Vec::new();
+    for suit1 in 0..4 {
+        for suit2 in 0..4 {
+            let card1 = 4 * high + suit1;
+            let card2 = 4 * low + suit2;
+            let valid = if high == low {
+                suit1 < suit2
+            } else {
+                (suit1 == suit2) == suited
+            };
+            if valid {
+                ret.push(combo_index(card1.min(card2), card1.max(card2)));
+            }
+        }
+    }
+    ret
+}
+
+/// 全てのハンドクラスを、ペア・スーテッド・オフスートの区別とともに列挙する
+fn all_classes() -> Vec<(usize, usize, bool)> {
+    let mut ret = Vec::with_capacity(169);
+    for high in 0..13 {
+        ret.push((high, high, false));
+        for low in 0..high {
+            ret.push((high, low, true));
+            ret.push((high, low, false));
+        }
+    }
+    ret
+}
+
+/// "AKs", "T9", "QQ" のようなハンドクラスの表記を `(高いランク, 低いランク, スート指定)` に変換する
+fn parse_class(s: &str) -> Result<(usize, usize, Option<bool>), String> {
+    let chars = s.chars().collect::<Vec<_>>();
+    let error = || format!("invalid hand class: '{}'", s);
+    if chars.len() < 2 || chars.len() > 3 {
+        return Err(error());
+    }
+
+    let rank1 = parse_rank(chars[0]).ok_or_else(error)?;
+    let rank2 = parse_rank(chars[1]).ok_or_else(error)?;
+    let suited = match chars.get(2) {
+        None => None,
+        Some('s') | Some('S') => Some(true),
+        Some('o') | Some('O') => Some(false),
+        Some(_) => return Err(error()),
+    };
+
+    if rank1 == rank2 && suited.is_some() {
+        return Err(error());
+    }
+
+    Ok((rank1.max(rank2), rank1.min(rank2), suited))
+}
+
+/// ハンドクラスの集合に属する手札の番号を列挙する
+fn expand_classes(classes: &[(usize, usize, Option<bool>)]) -> Vec<usize> {
+    let mut ret = Vec::new();
+    for &(high, low, suited) in classes {
+        match suited {
+            _ if high == low => ret.extend(class_combos(high, low, false)),
+            Some(suited) => ret.extend(class_combos(high, low, suited)),
+            None => {
+                ret.extend(class_combos(high, low, true));
+                ret.extend(class_combos(high, low, false));
+            }
+        }
+    }
+    ret
+}
+
+/// 重みを含まないレンジの要素を手札の番号の列に変換する
+fn parse_element(s: &str) -> Result<Vec<usize>, String> {
+    // 個別の手札 (例: "AsKh")
+    if s.len() == 4 && s.is_ascii() {
+        if let (Some(card1), Some(card2)) = (parse_card(&s[0..2]), parse_card(&s[2..4])) {
+            if card1 == card2 {
+                return Err(format!("duplicate card: '{}'", s));
+            }
+            return Ok(vec![combo_index(card1.min(card2), card1.max(card2))]);
+        }
+    }
+
+    // 範囲指定 (例: "99-66", "A5s-A2s")
+    if let Some((first, last)) = s.split_once('-') {
+        let (high1, low1, suited1) = parse_class(first)?;
+        let (high2, low2, suited2) = parse_class(last)?;
+        let classes = if high1 == low1 && high2 == low2 {
+            (high1.min(high2)..=high1.max(high2))
+                .map(|rank| (rank, rank, None))
+                .collect::<Vec<_>>()
+        } else if high1 == high2 && high1 != low1 && high2 != low2 && suited1 == suited2 {
+            (low1.min(low2)..=low1.max(low2))
+                .map(|low| (high1, low, suited1))
+                .collect::<Vec<_>>()
+        } else {
+            return Err(format!("invalid range: '{}'", s));
+        };
+        return Ok(expand_classes(&classes));
+    }
+
+    // 以上指定 (例: "22+", "A2s+")
+    if let Some(class) = s.strip_suffix('+') {
+        let (high, low, suited) = parse_class(class)?;
+        let classes = if high == low {
+            (high..13)
+                .map(|rank| (rank, rank, None))
+                .collect::<Vec<_>>()
+        } else {
+            (low..high)
+                .map(|low| (high, low, suited))
+                .collect::<Vec<_>>()
+        };
+        return Ok(expand_classes(&classes));
+    }
+
+    // 単一のハンドクラス (例: "AKs", "AK", "QQ")
+    Ok(expand_classes(&[parse_class(s)?]))
+}
+
+/// "22+, A2s+, KTo+, 76s:0.5" のようなレンジの表記を手札毎の重みに変換する
+///
+/// 重みは `:` に続けて指定し、省略した場合は1となる。同じ手札が複数回指定された場合は
+/// 後の指定が優先され、指定されなかった手札の重みは0となる。
+pub fn parse_range(s: &str) -> Result<Vec<f64>, String> {
+    let mut weights = vec![0.0; NUM_COMBOS];
+
+    for token in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        let (element, weight) = match token.split_once(':') {
+            Some((element, weight)) => {
+                let weight = weight
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid weight: '{}'", token))?;
+                if !(0.0..=1.0).contains(&weight) {
+                    return Err(format!("weight out of range: '{}'", token));
+                }
+                (element.trim(), weight)
+            }
+            None => (token, 1.0),
+        };
+
+        for index in parse_element(element)? {
+            weights[index] = weight;
+        }
+    }
+
+    Ok(weights)
+}
+
+/// 重みを文字列に変換する (重みが1の場合は空文字列)
+fn format_weight(weight: f64) -> String {
+    if weight == 1.0 {
+        String::new()
+    } else {
+        format!(":{}", weight)
+    }
+}
+
+/// 同じランクのカードを持つハンドクラスの連続した区間を表記に変換する
+///
+/// `kickers` は降順に並んだ連続するキッカー (ペアの場合はランク) で、`top` はその最大値が
+/// 取り得る値の上限である
+fn format_run(high: Option<usize>, kickers: &[usize], top: usize, suffix: &str) -> String {
+    let name = |kicker: usize| match high {
+        None => format!("{}{}", RANK_CHARS[kicker], RANK_CHARS[kicker]),
+        Some(high) => format!("{}{}{}", RANK_CHARS[high], RANK_CHARS[kicker], suffix),
+    };
+    let first = kickers[0];
+    let last = *kickers.last().unwrap();
+    if first == top && kickers.len() > 1 {
+        format!("{}+", name(last))
+    } else if kickers.len() == 1 {
+        name(first)
+    } else {
+        format!("{}-{}", name(first), name(last))
+    }
+}
+
+/// 降順に並んだランクの列を連続した区間に分割する
+fn split_runs(ranks: &[usize]) -> Vec<Vec<usize>> {
+    let mut runs: Vec<Vec<usize>> = Vec::new();
+    for &rank in ranks {
+        match runs.last_mut() {
+            Some(run) if *run.last().unwrap() == rank + 1 => run.push(rank),
+            _ => runs.push(vec![rank]),
+        }
+    }
+    runs
+}
+
+/// 手札毎の重み `weights` を、それと等価な短いレンジの表記に変換する
+///
+/// 各ハンドクラスは最も多くの手札が持つ重みでまとめて表記され、それと異なる重みを持つ手札は
+/// 個別に後置される。`parse_range()` で読み込むと `weights` と完全に一致する重みが得られる。
+pub fn format_range(weights: &[f64]) -> String {
+    assert_eq!(weights.len(), NUM_COMBOS);
+
+    // ハンドクラス毎に代表の重みを決める
+    let mut class_weight = BTreeMap::new();
+    let mut exceptions = Vec::new();
+    for (high, low, suited) in all_classes() {
+        let combos = class_combos(high, low, suited);
+        let mut best = (0, 0.0);
+        for &index in &combos {
+            let weight = weights[index];
+            let count = combos.iter().filter(|&&k| weights[k] == weight).count();
+            if count > best.0 || (count == best.0 && weight == 0.0) {
+                best = (count, weight);
+            }
+        }
+        class_weight.insert((high, low, suited), best.1);
+        for &index in &combos {
+            if weights[index] != best.1 {
+                exceptions.push(index);
+            }
+        }
+    }
+
+    let mut tokens = Vec::new();
+    let weight_keys = |filter: &dyn Fn(&(usize, usize, bool)) -> bool| {
+        let mut keys = class_weight
+            .iter()
+            .filter(|(class, w)| filter(class) && **w != 0.0)
+            .map(|(_, w)| *w)
+            .collect::<Vec<f64>>();
+        keys.sort_by(|a, b| b.partial_cmp(a).unwrap());
+        keys.dedup();
+        keys
+    };
+
+    // ペア
+    let mut pair_tokens = Vec::new();
+    for weight in weight_keys(&|&(high, low, _)| high == low) {
+        let ranks = (0..13)
+            .rev()
+            .filter(|&rank| class_weight[&(rank, rank, false)] == weight)
+            .collect::<Vec<_>>();
+        for run in split_runs(&ranks) {
+            let token = format_run(None, &run, 12, "") + &format_weight(weight);
+            pair_tokens.push((run[0], token));
+        }
+    }
+    pair_tokens.sort_by_key(|token| std::cmp::Reverse(token.0));
+    tokens.extend(pair_tokens.into_iter().map(|(_, token)| token));
+
+    // ペア以外 (高いランク毎)
+    for high in (1..13).rev() {
+        let mut high_tokens = Vec::new();
+        for weight in weight_keys(&|&(h, l, _)| h == high && l != high) {
+            let kickers = |suited: bool| {
+                (0..high)
+                    .rev()
+                    .filter(|&low| class_weight[&(high, low, suited)] == weight)
+                    .collect::<Vec<_>>()
+            };
+            let suited_runs = split_runs(&kickers(true));
+            let offsuit_runs = split_runs(&kickers(false));
+            for run in &suited_runs {
+                let suffix = if offsuit_runs.contains(run) { "" } else { "s" };
+                let token = format_run(Some(high), run, high - 1, suffix) + &format_weight(weight);
+                high_tokens.push((run[0], suffix.is_empty(), token));
+            }
+            for run in offsuit_runs.iter().filter(|run| !suited_runs.contains(run)) {
+                let token = format_run(Some(high), run, high - 1, "o") + &format_weight(weight);
+                high_tokens.push((run[0], false, token));
+            }
+        }
+        high_tokens.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+        tokens.extend(high_tokens.into_iter().map(|(_, _, token)| token));
+    }
+
+    // 個別の手札
+    for index in exceptions {
+        tokens.push(combo_to_string(index) + &format_weight(weights[index]));
+    }
+
+    tokens.join(", ")
+}
+
+/// ヘッズアップのゲームにおける各プレイヤー (0: スモールブラインド、1: ビッグブラインド) の
+/// 初期レンジ (手札毎の重み)
+#[derive(Clone, Debug, PartialEq)]
+pub struct InitialRanges {
+    ranges: [Vec<f64>; 2],
+}
+
+impl Default for InitialRanges {
+    fn default() -> Self {
+        Self {
+            ranges: [vec![1.0; NUM_COMBOS], vec![1.0; NUM_COMBOS]],
+        }
+    }
+}
+
+impl InitialRanges {
+    /// パラメータの名前
+    const NAMES: [&'static str; 2] = ["sb_range", "bb_range"];
+
+    /// `player` の初期レンジを返す
+    #[inline]
+    pub fn get(&self, player: usize) -> &[f64] {
+        &self.ranges[player]
+    }
+
+    /// `player` の初期レンジを設定する (`range` の各要素は手札が実現している確率)
+    pub fn set(&mut self, player: usize, range: Vec<f64>) {
+        assert_eq!(range.len(), NUM_COMBOS);
+        assert!(range.iter().all(|&w| (0.0..=1.0).contains(&w)));
+        self.ranges[player] = range;
+    }
+
+    /// 事前の絞り込みがない (全ての重みが1である) かどうかを返す
+    #[inline]
+    pub fn is_uniform(&self) -> bool {
+        self.ranges.iter().flatten().all(|&w| w == 1.0)
+    }
+
+    /// `Game::parameters()` に含めるパラメータの列を返す (絞り込みのないレンジは含めない)
+    pub fn parameters(&self) -> Vec<(String, String)> {
+        (0..2)
+            .filter(|&player| self.ranges[player].iter().any(|&w| w != 1.0))
+            .map(|player| {
+                (
+                    Self::NAMES[player].to_string(),
+                    format_range(&self.ranges[player]),
+                )
+            })
+            .collect()
+    }
+
+    /// パラメータの列から初期レンジを読み取る (存在しないプレイヤーのレンジは絞り込みなしとする)
+    pub fn from_parameters(parameters: &[(String, String)]) -> Result<Self, String> {
+        let mut ret = Self::default();
+        for (player, name) in Self::NAMES.iter().enumerate() {
+            if let Some((_, value)) = parameters.iter().find(|(k, _)| k == name) {
+                let range = parse_range(value)
+                    .map_err(|e| format!("invalid value of parameter '{}': {}", name, e))?;
+                ret.set(player, range);
+            }
+        }
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 重みが `weight` である手札の個数を返す
+    fn count(weights: &[f64], weight: f64) -> usize {
+        weights.iter().filter(|&&w| w == weight).count()
+    }
+
+    #[test]
+    fn parse_plus_and_dash() {
+        assert_eq!(count(&parse_range("22+").unwrap(), 1.0), 13 * 6);
+        assert_eq!(count(&parse_range("A2s+").unwrap(), 1.0), 12 * 4);
+        assert_eq!(count(&parse_range("KTo+").unwrap(), 1.0), 3 * 12);
+        assert_eq!(count(&parse_range("99-66").unwrap(), 1.0), 4 * 6);
+        assert_eq!(count(&parse_range("A5s-A2s").unwrap(), 1.0), 4 * 4);
+        assert_eq!(count(&parse_range("AK").unwrap(), 1.0), 16);
+        assert_eq!(
+            parse_range("KTo+").unwrap(),
+            parse_range("KQo, KJo, KTo").unwrap()
+        );
+        assert_eq!(
+            parse_range("A2s-A5s").unwrap(),
+            parse_range("A5s-A2s").unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_weights() {
+        let weights = parse_range("QQ+, 76s:0.5, AsKh:0.25, QQ:0").unwrap();
+        assert_eq!(count(&weights, 1.0), 12);
+        assert_eq!(count(&weights, 0.5), 4);
+        assert_eq!(count(&weights, 0.25), 1);
+        assert!(parse_range("AA:1.5").is_err());
+        assert!(parse_range("AsAs").is_err());
+        assert!(parse_range("AKs-QJs").is_err());
+    }
+
+    #[test]
+    fn format_round_trip() {
+        for s in [
+            "22+, A2s+, KTo+, 76s:0.5",
+            "99-66, A5s-A2s, KQ, T9s:0.75, AsKh:0.25",
+            "AA, KK:0.3, AKs, AhKd, 2c2d:0.1",
+            "",
+        ] {
+            let weights = parse_range(s).unwrap();
+            let formatted = format_range(&weights);
+            assert_eq!(parse_range(&formatted).unwrap(), weights, "{}", formatted);
+        }
+
+        let weights = (0..NUM_COMBOS)
+            .map(|combo| (combo % 7) as f64 / 6.0)
+            .collect::<Vec<_>>();
+        assert_eq!(parse_range(&format_range(&weights)).unwrap(), weights);
+    }
+
+    #[test]
+    fn initial_ranges_parameters() {
+        let mut ranges = InitialRanges::default();
+        assert!(ranges.parameters().is_empty());
+        ranges.set(1, parse_range("55+, AT+").unwrap());
+        let parameters = ranges.parameters();
+        assert_eq!(parameters.len(), 1);
+        assert_eq!(InitialRanges::from_parameters(&parameters).unwrap(), ranges);
+    }
+}
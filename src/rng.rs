@@ -0,0 +1,76 @@
+/// シード値から再現可能な乱数列を生成する擬似乱数生成器 (xoshiro256**)
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    /// シード値 `seed` から乱数生成器を初期化する (内部状態は SplitMix64 で生成する)
+    pub fn new(seed: u64) -> Self {
+        let mut x = seed;
+        let mut split_mix = || {
+            x = x.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+        Self {
+            state: [split_mix(), split_mix(), split_mix(), split_mix()],
+        }
+    }
+
+    /// 64ビットの一様乱数を返す
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let ret = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        ret
+    }
+
+    /// 0以上1未満の一様乱数を返す
+    #[inline]
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// 0以上 `n` 未満の一様な整数を返す
+    #[inline]
+    pub fn gen_range(&mut self, n: usize) -> usize {
+        assert!(n > 0);
+        let n = n as u64;
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return (x % n) as usize;
+            }
+        }
+    }
+
+    /// 重み `weights` に比例する確率で添字を返す (重みの和が0の場合は一様に選ぶ)
+    pub fn choose_weighted(&mut self, weights: &[f64]) -> usize {
+        let sum = weights.iter().sum::<f64>();
+        if sum <= 0.0 {
+            return self.gen_range(weights.len());
+        }
+
+        let mut x = self.next_f64() * sum;
+        for (index, &weight) in weights.iter().enumerate() {
+            if x < weight {
+                return index;
+            }
+            x -= weight;
+        }
+
+        // 丸め誤差対策として、正の重みを持つ最後の添字を返す
+        weights.iter().rposition(|&w| w > 0.0).unwrap()
+    }
+}
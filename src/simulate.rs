@@ -0,0 +1,170 @@
+use crate::export::collect_nodes;
+use crate::interface::*;
+use crate::rng::*;
+use crate::strategy::*;
+use crate::util::*;
+use rayon::prelude::*;
+
+/// 1つの乱数生成器が担当する配り方の個数
+const CHUNK_SIZE: usize = 10000;
+
+/// 対戦シミュレーションの設定
+#[derive(Clone, Debug)]
+pub struct MatchConfig {
+    /// 配り方の個数
+    pub num_deals: usize,
+
+    /// 乱数のシード値
+    pub seed: u64,
+
+    /// 同じ配り方を席を入れ替えて2回プレイするか (duplicate 方式)
+    pub duplicate: bool,
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        Self {
+            num_deals: 1_000_000,
+            seed: 0,
+            duplicate: true,
+        }
+    }
+}
+
+/// 対戦シミュレーションの結果
+#[derive(Clone, Debug)]
+pub struct MatchResult {
+    /// プレイしたハンドの個数
+    pub num_hands: usize,
+
+    /// 戦略 A の1ハンドあたりの平均利得
+    pub mean: f64,
+
+    /// 平均利得の標準誤差
+    pub std_error: f64,
+}
+
+impl MatchResult {
+    /// 平均利得の 95% 信頼区間を返す
+    #[inline]
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        let half_width = 1.96 * self.std_error;
+        (self.mean - half_width, self.mean + half_width)
+    }
+}
+
+/// 初期レンジと偶然手番の分布に従って手札を配る (棄却サンプリング)
+///
+/// 初期レンジのもとで実現し得る配り方が存在することを呼び出し側で確認する必要がある
+fn deal<T: Game>(game: &T, initial_reach: &[Vec<f64>; 2], rng: &mut Rng) -> [usize; 2] {
+    let num_private_hands = game.num_private_hands();
+    loop {
+        let hands = [
+            rng.gen_range(num_private_hands),
+            rng.gen_range(num_private_hands),
+        ];
+        let accept = initial_reach[0][hands[0]] * initial_reach[1][hands[1]];
+        if game.is_valid_deal(hands) && rng.next_f64() < accept {
+            return hands;
+        }
+    }
+}
+
+/// 戦略 `strategies[p]` をプレイヤー `p` が用いて1ハンドをプレイし、`player` の利得を返す
+///
+/// 最後の偶然手番 (ボードなど) は期待値で置き換えられる
+fn play_hand<T: Game>(
+    game: &T,
+    strategies: [&Strategy; 2],
+    hands: [usize; 2],
+    player: usize,
+    rng: &mut Rng,
+) -> f64 {
+    let mut node = game.root();
+    while !node.is_terminal() {
+        let current_player = node.current_player();
+        let probs =
+            strategies[current_player].action_probs(node.public_history(), hands[current_player]);
+        node = node.play(rng.choose_weighted(&probs));
+    }
+    game.evaluate_hands(&node, player, hands)
+}
+
+/// 戦略 `a` と戦略 `b` の対戦をシミュレーションし、`a` の平均利得を返す
+///
+/// `config.duplicate` が真の場合は、同じ手札の配り方で `a` と `b` の席を入れ替えてプレイし、
+/// 両者の平均を1つの標本とすることで手札の運による分散を打ち消す。
+/// また、最後の偶然手番は `Game::evaluate_hands()` によって期待値で置き換えられる。
+///
+/// 初期レンジのもとで実現し得る配り方が存在しない場合はエラーを返す
+pub fn simulate_match<T: Game + Sync>(
+    game: &T,
+    a: &Strategy,
+    b: &Strategy,
+    config: &MatchConfig,
+) -> Result<MatchResult, String> {
+    if joint_reach(game) <= 0.0 {
+        return Err("the initial ranges allow no valid deal".to_string());
+    }
+    let initial_reach = [game.initial_reach(0), game.initial_reach(1)];
+    let num_chunks = config.num_deals.div_ceil(CHUNK_SIZE);
+
+    // 各チャンクで (標本の和, 標本の二乗和) を計算する
+    let (sum, sum_sq) = (0..num_chunks)
+        .into_par_iter()
+        .map(|chunk| {
+            let mut rng = Rng::new(config.seed ^ (chunk as u64).wrapping_mul(0x9e3779b97f4a7c15));
+            let num_deals = CHUNK_SIZE.min(config.num_deals - chunk * CHUNK_SIZE);
+            let mut sum = 0.0;
+            let mut sum_sq = 0.0;
+            for deal_index in 0..num_deals {
+                let hands = deal(game, &initial_reach, &mut rng);
+                let sample = if config.duplicate {
+                    let seat0 = play_hand(game, [a, b], hands, 0, &mut rng);
+                    let seat1 = play_hand(game, [b, a], hands, 1, &mut rng);
+                    0.5 * (seat0 + seat1)
+                } else {
+                    let seat = (chunk * CHUNK_SIZE + deal_index) % 2;
+                    let strategies = if seat == 0 { [a, b] } else { [b, a] };
+                    play_hand(game, strategies, hands, seat, &mut rng)
+                };
+                sum += sample;
+                sum_sq += sample * sample;
+            }
+            (sum, sum_sq)
+        })
+        .reduce(|| (0.0, 0.0), |x, y| (x.0 + y.0, x.1 + y.1));
+
+    let n = config.num_deals as f64;
+    let mean = sum / n;
+    let variance = (sum_sq / n - mean * mean).max(0.0) * n / (n - 1.0).max(1.0);
+
+    Ok(MatchResult {
+        num_hands: config.num_deals * if config.duplicate { 2 } else { 1 },
+        mean,
+        std_error: (variance / n).sqrt(),
+    })
+}
+
+/// プレイヤー `p` の手番で `strategies[p]` を用いる戦略の組を返す
+pub fn combine_strategies<T: Game>(game: &T, strategies: [&Strategy; 2]) -> Strategy {
+    let mut nodes = Vec::new();
+    collect_nodes(game.root(), &mut nodes);
+
+    let mut ret = strategies[0].clone();
+    for node in nodes {
+        let history = node.public_history();
+        ret.set(
+            history.clone(),
+            strategies[node.current_player()][history].clone(),
+        );
+    }
+    ret
+}
+
+/// 戦略 `a` と戦略 `b` が席を入れ替えて対戦するときの `a` の利得の期待値を厳密に計算する
+pub fn compute_match_ev<T: Game>(game: &T, a: &Strategy, b: &Strategy) -> f64 {
+    let ev0 = compute_ev(game, 0, &combine_strategies(game, [a, b]));
+    let ev1 = compute_ev(game, 1, &combine_strategies(game, [b, a]));
+    0.5 * (ev0 + ev1)
+}
@@ -0,0 +1,303 @@
+use crate::interface::*;
+use bincode::Options;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::ops::Index;
+
+/// 保存形式のバージョン
+const FORMAT_VERSION: u32 = 1;
+
+/// 戦略の付加情報を表す構造体
+#[derive(Clone, Debug, Default)]
+pub struct StrategyMetadata {
+    /// ゲームの名前
+    pub game_name: String,
+
+    /// ゲームのパラメータ (名前と値の組)
+    pub parameters: Vec<(String, String)>,
+
+    /// 学習の反復回数
+    pub num_iterations: usize,
+
+    /// 可搾取量 (未計算の場合は `None`)
+    pub exploitability: Option<f64>,
+
+    /// 用いたソルバーの名前
+    pub solver: String,
+}
+
+/// パラメータの列 `parameters` から名前が `name` のパラメータを読み取る
+pub fn parse_parameter<T: std::str::FromStr>(
+    parameters: &[(String, String)],
+    name: &str,
+) -> Result<T, String> {
+    let value = parameters
+        .iter()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v)
+        .ok_or_else(|| format!("missing parameter '{}'", name))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value of parameter '{}': '{}'", name, value))
+}
+
+/// パラメータの列 `parameters` から名前が `name` のパラメータを読み取る
+/// (存在しない場合は `default` を返す)
+pub fn parse_parameter_or<T: std::str::FromStr>(
+    parameters: &[(String, String)],
+    name: &str,
+    default: T,
+) -> Result<T, String> {
+    match parameters.iter().any(|(k, _)| k == name) {
+        true => parse_parameter(parameters, name),
+        false => Ok(default),
+    }
+}
+
+impl StrategyMetadata {
+    /// 名前が `name` のパラメータの値を返す
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// 戦略の組 (各パブリックな履歴における、アクション毎・手札毎の確率) を表す構造体
+#[derive(Clone, Debug, Default)]
+pub struct Strategy {
+    /// パブリックな履歴から `[action][hand]` の確率表への写像
+    profile: BTreeMap<PublicHistory, Vec<Vec<f64>>>,
+
+    /// 付加情報
+    pub metadata: StrategyMetadata,
+}
+
+impl Strategy {
+    /// コンストラクタ
+    pub fn new(profile: HashMap<PublicHistory, Vec<Vec<f64>>>, metadata: StrategyMetadata) -> Self {
+        Self {
+            profile: profile.into_iter().collect(),
+            metadata,
+        }
+    }
+
+    /// 履歴 `history` における戦略を返す
+    #[inline]
+    pub fn get(&self, history: &[Action]) -> Option<&Vec<Vec<f64>>> {
+        self.profile.get(history)
+    }
+
+    /// 履歴 `history` において手札 `hand` を持つときの各アクションの確率を返す
+    pub fn action_probs(&self, history: &[Action], hand: usize) -> Vec<f64> {
+        self.profile[history]
+            .iter()
+            .map(|probs| probs[hand])
+            .collect()
+    }
+
+    /// 履歴 `history` において手札 `hand` を持つときに `action` を選ぶ確率を返す
+    #[inline]
+    pub fn action_prob(&self, history: &[Action], action: Action, hand: usize) -> f64 {
+        self.profile[history][action][hand]
+    }
+
+    /// 履歴の辞書順に、履歴と戦略の組を列挙する
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&PublicHistory, &Vec<Vec<f64>>)> {
+        self.profile.iter()
+    }
+
+    /// 戦略を持つ履歴の個数を返す
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.profile.len()
+    }
+
+    /// 戦略を持つ履歴が存在しないかどうかを返す
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.profile.is_empty()
+    }
+
+    /// 履歴 `history` における戦略を `probs` で置き換える
+    pub fn set(&mut self, history: PublicHistory, probs: Vec<Vec<f64>>) {
+        self.profile.insert(history, probs);
+    }
+
+    /// 戦略を `path` に bincode 形式で保存する
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let meta = &self.metadata;
+        let data = (
+            FORMAT_VERSION,
+            &meta.game_name,
+            &meta.parameters,
+            meta.num_iterations as u64,
+            meta.exploitability,
+            &meta.solver,
+            &self.profile,
+        );
+        let writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(writer, &data).map_err(to_io_error)
+    }
+
+    /// `path` に bincode 形式で保存された戦略を読み込む
+    pub fn load(path: &str) -> io::Result<Self> {
+        type Data = (
+            u32,
+            String,
+            Vec<(String, String)>,
+            u64,
+            Option<f64>,
+            String,
+            BTreeMap<PublicHistory, Vec<Vec<f64>>>,
+        );
+        let file = File::open(path)?;
+
+        // 壊れたファイルの長さの値によって巨大な領域を確保しないように、読み込む量をファイルの大きさで制限する
+        let options = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(file.metadata()?.len());
+        let data: Data = options
+            .deserialize_from(BufReader::new(file))
+            .map_err(to_io_error)?;
+
+        if data.0 != FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported strategy format version: {}", data.0),
+            ));
+        }
+
+        Ok(Self {
+            profile: data.6,
+            metadata: StrategyMetadata {
+                game_name: data.1,
+                parameters: data.2,
+                num_iterations: data.3 as usize,
+                exploitability: data.4,
+                solver: data.5,
+            },
+        })
+    }
+}
+
+impl Index<&PublicHistory> for Strategy {
+    type Output = Vec<Vec<f64>>;
+
+    #[inline]
+    fn index(&self, history: &PublicHistory) -> &Self::Output {
+        &self.profile[history]
+    }
+}
+
+#[inline]
+fn to_io_error(err: bincode::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// テスト毎に異なる一時ファイルのパスを返す
+    fn temp_path(name: &str) -> String {
+        let mut path = std::env::temp_dir();
+        path.push(format!(
+            "yabai-strategy-{}-{}.bin",
+            std::process::id(),
+            name
+        ));
+        path.to_str().unwrap().to_string()
+    }
+
+    fn sample_strategy() -> Strategy {
+        let mut profile = HashMap::new();
+        profile.insert(vec![], vec![vec![0.25, 1.0, 0.0], vec![0.75, 0.0, 1.0]]);
+        profile.insert(vec![1], vec![vec![0.5, 0.125, 1.0], vec![0.5, 0.875, 0.0]]);
+        let metadata = StrategyMetadata {
+            game_name: "push-fold".to_string(),
+            parameters: vec![("eff-stack".to_string(), "10".to_string())],
+            num_iterations: 1000,
+            exploitability: Some(0.0125),
+            solver: "cfr+".to_string(),
+        };
+        Strategy::new(profile, metadata)
+    }
+
+    /// `bytes` を一時ファイルに書き込んで読み込んだ結果を返す
+    fn load_bytes(name: &str, bytes: &[u8]) -> io::Result<Strategy> {
+        let path = temp_path(name);
+        std::fs::write(&path, bytes).unwrap();
+        let ret = Strategy::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        ret
+    }
+
+    #[test]
+    fn round_trip() {
+        let strategy = sample_strategy();
+        let path = temp_path("round-trip");
+        strategy.save(&path).unwrap();
+        let loaded = Strategy::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.profile, strategy.profile);
+        let (a, b) = (&loaded.metadata, &strategy.metadata);
+        assert_eq!(a.game_name, b.game_name);
+        assert_eq!(a.parameters, b.parameters);
+        assert_eq!(a.num_iterations, b.num_iterations);
+        assert_eq!(a.exploitability, b.exploitability);
+        assert_eq!(a.solver, b.solver);
+    }
+
+    #[test]
+    fn rejects_truncated_file() {
+        let path = temp_path("truncated");
+        sample_strategy().save(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        for len in 0..bytes.len() {
+            assert!(
+                load_bytes("truncated", &bytes[..len]).is_err(),
+                "len = {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_corrupted_file() {
+        let path = temp_path("corrupted");
+        sample_strategy().save(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // 未知のバージョン
+        let mut corrupted = bytes.clone();
+        corrupted[0] ^= 0xff;
+        assert!(load_bytes("corrupted", &corrupted).is_err());
+
+        // ゲーム名の長さが巨大な値
+        let mut corrupted = bytes.clone();
+        corrupted[4..12].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(load_bytes("corrupted", &corrupted).is_err());
+
+        // 不正な UTF-8 のゲーム名
+        let mut corrupted = bytes.clone();
+        corrupted[12] = 0xff;
+        assert!(load_bytes("corrupted", &corrupted).is_err());
+
+        // どのバイトが壊れていてもパニックしない
+        for i in 0..bytes.len() {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= 0x80;
+            let _ = load_bytes("corrupted", &corrupted);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use crate::cfr::*;
+use crate::game_push_fold::*;
+use crate::grid::*;
+use crate::util::*;
+use rayon::prelude::*;
+use std::io::{self, Write};
+
+/// 有効スタックの掃引の設定
+#[derive(Clone, Debug)]
+pub struct SweepConfig {
+    /// 有効スタックの最小値 (単位: bb)
+    pub min_stack: f64,
+
+    /// 有効スタックの最大値 (単位: bb)
+    pub max_stack: f64,
+
+    /// 有効スタックの刻み幅 (単位: bb)
+    pub step: f64,
+
+    /// ブラインドとアンティの構造
+    pub blinds: BlindStructure,
+
+    /// 各スタックでの CFR の反復回数
+    pub num_iterations: usize,
+
+    /// CFRアルゴリズムの変種
+    pub variant: SolverVariant,
+}
+
+impl Default for SweepConfig {
+    fn default() -> Self {
+        Self {
+            min_stack: 1.0,
+            max_stack: 30.0,
+            step: 0.1,
+            blinds: BlindStructure::default(),
+            num_iterations: 1000,
+            variant: SolverVariant::default(),
+        }
+    }
+}
+
+impl SweepConfig {
+    /// 掃引する有効スタックの一覧を昇順に返す
+    pub fn stacks(&self) -> Vec<f64> {
+        assert!(self.step > 0.0 && self.min_stack <= self.max_stack);
+        let num_steps = ((self.max_stack - self.min_stack) / self.step + 1e-9).floor() as usize;
+        (0..=num_steps)
+            .map(|i| {
+                // 刻み幅の累積による丸め誤差を取り除く
+                let stack = self.min_stack + i as f64 * self.step;
+                (stack * 1e9).round() / 1e9
+            })
+            .collect()
+    }
+}
+
+/// 有効スタック毎のプッシュ/フォールドの解を表す構造体
+#[derive(Clone, Debug)]
+pub struct StackSweep {
+    /// 有効スタックの一覧 (昇順)
+    pub stacks: Vec<f64>,
+
+    /// 各スタックにおける手札毎のプッシュ確率 (`[stack][combo]`)
+    pub push: Vec<Vec<f64>>,
+
+    /// 各スタックにおける手札毎のコール確率 (`[stack][combo]`)
+    pub call: Vec<Vec<f64>>,
+
+    /// 各スタックにおける可搾取量
+    pub exploitability: Vec<f64>,
+}
+
+/// 有効スタックを掃引しながらプッシュ/フォールドを解く (スタック毎に並列に計算する)
+pub fn sweep_push_fold(config: &SweepConfig) -> StackSweep {
+    let results = config
+        .stacks()
+        .into_par_iter()
+        .map(|stack| {
+            let game = PushFoldGame::with_blinds(stack, config.blinds);
+            let mut cfr = CFRMinimizer::with_variant(&game, config.variant);
+            let strategy = cfr.compute(config.num_iterations);
+            let exploitability = compute_exploitability(&game, &strategy);
+            (
+                stack,
+                strategy[&vec![]][1].clone(),
+                strategy[&vec![1]][1].clone(),
+                exploitability,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut ret = StackSweep {
+        stacks: Vec::with_capacity(results.len()),
+        push: Vec::with_capacity(results.len()),
+        call: Vec::with_capacity(results.len()),
+        exploitability: Vec::with_capacity(results.len()),
+    };
+    for (stack, push, call, exploitability) in results {
+        ret.stacks.push(stack);
+        ret.push.push(push);
+        ret.call.push(call);
+        ret.exploitability.push(exploitability);
+    }
+    ret
+}
+
+impl StackSweep {
+    /// `player` (0: プッシュする側、1: コールする側) の各スタックにおける確率を返す
+    #[inline]
+    fn probs(&self, player: usize) -> &Vec<Vec<f64>> {
+        match player {
+            0 => &self.push,
+            _ => &self.call,
+        }
+    }
+
+    /// `player` の各スタックにおけるハンドクラス毎の確率をグリッドにして返す
+    pub fn grids(&self, player: usize) -> Vec<HandGrid> {
+        self.probs(player)
+            .iter()
+            .map(|probs| HandGrid::from_combos(probs))
+            .collect()
+    }
+
+    /// `player` のハンドクラス毎のスタックの閾値をグリッドにして返す
+    ///
+    /// 閾値はハンドクラスの (コンボ数で平均した) 確率が 0.5 以上となる最大の有効スタックで、
+    /// どのスタックでもプッシュ (コール) しないハンドクラスは NaN となる。
+    /// 閾値が掃引の最大値に等しい場合は「最大値以上」を意味する。
+    pub fn thresholds(&self, player: usize) -> HandGrid {
+        let grids = self.grids(player);
+        let mut cells = [[f64::NAN; 13]; 13];
+        for (row, cells_row) in cells.iter_mut().enumerate() {
+            for (col, cell) in cells_row.iter_mut().enumerate() {
+                if let Some(index) = grids.iter().rposition(|g| g.get(row, col) >= 0.5) {
+                    *cell = self.stacks[index];
+                }
+            }
+        }
+        HandGrid::from_cells(cells)
+    }
+
+    /// ハンドクラス毎の閾値を `hand,push_threshold,call_threshold` の CSV 形式で書き出す
+    ///
+    /// 閾値を持たないハンドクラスの値は空欄となる
+    pub fn write_thresholds_csv<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let thresholds = [self.thresholds(0), self.thresholds(1)];
+        let field = |value: f64| match value.is_nan() {
+            true => String::new(),
+            false => value.to_string(),
+        };
+
+        writeln!(writer, "hand,push_threshold,call_threshold")?;
+        for row in 0..13 {
+            for col in 0..13 {
+                writeln!(
+                    writer,
+                    "{},{},{}",
+                    HandGrid::class_name(row, col),
+                    field(thresholds[0].get(row, col)),
+                    field(thresholds[1].get(row, col))
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// スタックとハンドクラス毎の確率を `stack,hand,push,call` の CSV 形式で書き出す
+    pub fn write_frequencies_csv<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let grids = [self.grids(0), self.grids(1)];
+
+        writeln!(writer, "stack,hand,push,call")?;
+        for (index, stack) in self.stacks.iter().enumerate() {
+            for row in 0..13 {
+                for col in 0..13 {
+                    writeln!(
+                        writer,
+                        "{},{},{},{}",
+                        stack,
+                        HandGrid::class_name(row, col),
+                        grids[0][index].get(row, col),
+                        grids[1][index].get(row, col)
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use crate::interface::*;
+use crate::strategy::*;
 use std::collections::HashMap;
 
 #[inline]
@@ -21,25 +22,93 @@ fn dot(lhs: &Vec<f64>, rhs: &Vec<f64>) -> f64 {
     lhs.iter().zip(rhs).map(|(l, r)| l * r).sum()
 }
 
+/// 各プレイヤーの初期レンジのもとで実現し得る配り方の確率の和を返す
+/// (初期レンジによる絞り込みがない場合は1、実現し得る配り方がない場合は0)
+pub fn joint_reach<T: Game>(game: &T) -> f64 {
+    let chance_reach = game.chance_reach(&game.initial_reach(1));
+    dot(&game.initial_reach(0), &chance_reach)
+}
+
 /// 戦略の組 `strategy` のもとでの `player` の利得の期待値を返す
-pub fn compute_ev<T: Game>(
-    game: &T,
-    player: usize,
-    strategy: &HashMap<PublicHistory, Vec<Vec<f64>>>,
-) -> f64 {
-    let ones = vec![1.0; T::num_private_hands()];
-    compute_ev_rec(game, &T::root(), player, &ones, &ones, strategy)
+///
+/// 初期レンジが与えられている場合は、初期レンジのもとで配られたことを条件とする期待値を返す
+pub fn compute_ev<T: Game>(game: &T, player: usize, strategy: &Strategy) -> f64 {
+    let pi = game.initial_reach(player);
+    let pmi = game.initial_reach(player ^ 1);
+    compute_ev_rec(game, &game.root(), player, &pi, &pmi, strategy) / joint_reach(game)
+}
+
+/// 戦略の組 `strategy` のもとで `player` が手札 `hand` を持つときの利得の期待値を、
+/// 手札毎に並べて返す (実現し得ない手札については0を返す)
+pub fn compute_ev_per_hand<T: Game>(game: &T, player: usize, strategy: &Strategy) -> Vec<f64> {
+    let pmi = game.initial_reach(player ^ 1);
+    let cfvalues = cfvalues_rec(game, &game.root(), player, &pmi, strategy);
+    let chance_reach = game.chance_reach(&pmi);
+    cfvalues
+        .iter()
+        .zip(&chance_reach)
+        .map(|(v, r)| if *r == 0.0 { 0.0 } else { v / r })
+        .collect()
 }
 
 /// 戦略の組 `strategy` の可搾取量を返す
-pub fn compute_exploitability<T: Game>(
+///
+/// 各プレイヤーが最適応答に切り替えたときの利得の増分の和 (NashConv) を返す。
+/// ゼロサムゲームでは最適応答の利得の和に等しい。
+/// ICM やレーキなどによってゼロサムでないゲームでも非負であり、0であることと
+/// `strategy` がナッシュ均衡であることは同値である。
+/// 利得は `compute_ev()` と同様に初期レンジのもとでの条件付き期待値であり、
+/// 異なる初期レンジの間でも比較できる
+pub fn compute_exploitability<T: Game>(game: &T, strategy: &Strategy) -> f64 {
+    let reach0 = game.initial_reach(0);
+    let reach1 = game.initial_reach(1);
+    let br0 = best_cfvalues_rec(game, &game.root(), 0, &reach1, strategy);
+    let br1 = best_cfvalues_rec(game, &game.root(), 1, &reach0, strategy);
+    let br_sum = (dot(&reach0, &br0) + dot(&reach1, &br1)) / joint_reach(game);
+    br_sum - compute_ev(game, 0, strategy) - compute_ev(game, 1, strategy)
+}
+
+/// 戦略の組 `strategy` に対する `player` の最適応答戦略 (純粋戦略) を返す
+///
+/// 返り値の戦略の組において、`player` 以外の手番では `strategy` と同じ戦略を用いる
+pub fn compute_best_response<T: Game>(game: &T, player: usize, strategy: &Strategy) -> Strategy {
+    let mut ret = strategy.clone();
+    for (history, probs) in best_response_map(game, player, strategy) {
+        ret.set(history, probs);
+    }
+    ret.metadata.solver = format!("Best response of player {}", player);
+    ret
+}
+
+/// 各プレイヤーが戦略の組 `strategy` における相手の戦略に対する最適応答を用いる戦略の組を返す
+pub fn compute_best_response_profile<T: Game>(game: &T, strategy: &Strategy) -> Strategy {
+    let mut ret = strategy.clone();
+    for player in 0..2 {
+        for (history, probs) in best_response_map(game, player, strategy) {
+            ret.set(history, probs);
+        }
+    }
+    ret.metadata.solver = "Best response".to_string();
+    ret
+}
+
+/// `player` の最適応答戦略を、パブリックな履歴から確率表への写像として返す
+fn best_response_map<T: Game>(
     game: &T,
-    strategy: &HashMap<PublicHistory, Vec<Vec<f64>>>,
-) -> f64 {
-    let ones = vec![1.0; T::num_private_hands()];
-    let br0 = best_cfvalues_rec(game, &T::root(), 0, &ones, strategy);
-    let br1 = best_cfvalues_rec(game, &T::root(), 1, &ones, strategy);
-    br0.iter().sum::<f64>() + br1.iter().sum::<f64>()
+    player: usize,
+    strategy: &Strategy,
+) -> HashMap<PublicHistory, Vec<Vec<f64>>> {
+    let pmi = game.initial_reach(player ^ 1);
+    let mut best_response = HashMap::new();
+    best_response_rec(
+        game,
+        &game.root(),
+        player,
+        &pmi,
+        strategy,
+        &mut best_response,
+    );
+    best_response
 }
 
 /// 利得の期待値を再帰的に計算するヘルパー
@@ -49,56 +118,148 @@ fn compute_ev_rec<T: Game>(
     player: usize,
     pi: &Vec<f64>,
     pmi: &Vec<f64>,
-    strategy: &HashMap<PublicHistory, Vec<Vec<f64>>>,
+    strategy: &Strategy,
 ) -> f64 {
     if node.is_terminal() {
-        return dot(&game.evaluate(node, player, pmi), &pi);
+        return dot(&game.evaluate(node, player, pmi), pi);
     }
 
     let current_strategy = &strategy[node.public_history()];
     if node.current_player() == player {
         node.actions()
             .map(|action| {
-                let pi = mul_vector(&current_strategy[action], &pi);
+                let pi = mul_vector(&current_strategy[action], pi);
                 compute_ev_rec(game, &node.play(action), player, &pi, pmi, strategy)
             })
             .sum()
     } else {
         node.actions()
             .map(|action| {
-                let pmi = mul_vector(&current_strategy[action], &pmi);
+                let pmi = mul_vector(&current_strategy[action], pmi);
                 compute_ev_rec(game, &node.play(action), player, pi, &pmi, strategy)
             })
             .sum()
     }
 }
 
-/// 最適応答戦略の counterfactual value を再帰的に計算するヘルパー
-fn best_cfvalues_rec<T: Game>(
+/// 戦略の組 `strategy` のもとでの counterfactual value を再帰的に計算するヘルパー
+fn cfvalues_rec<T: Game>(
     game: &T,
     node: &T::Node,
     player: usize,
     pmi: &Vec<f64>,
-    strategy: &HashMap<PublicHistory, Vec<Vec<f64>>>,
+    strategy: &Strategy,
 ) -> Vec<f64> {
     if node.is_terminal() {
         return game.evaluate(node, player, pmi);
     }
 
+    let current_strategy = &strategy[node.public_history()];
     if node.current_player() == player {
         node.actions()
             .map(|action| {
-                best_cfvalues_rec(game, &node.play(action), player, pmi, strategy)
+                let cfvalues = cfvalues_rec(game, &node.play(action), player, pmi, strategy);
+                mul_vector(&cfvalues, &current_strategy[action])
+            })
+            .reduce(|v, w| add_vector(&v, &w))
+    } else {
+        node.actions()
+            .map(|action| {
+                let pmi = mul_vector(pmi, &current_strategy[action]);
+                cfvalues_rec(game, &node.play(action), player, &pmi, strategy)
             })
+            .reduce(|v, w| add_vector(&v, &w))
+    }
+    .unwrap()
+}
+
+/// 最適応答戦略の counterfactual value を再帰的に計算するヘルパー
+fn best_cfvalues_rec<T: Game>(
+    game: &T,
+    node: &T::Node,
+    player: usize,
+    pmi: &Vec<f64>,
+    strategy: &Strategy,
+) -> Vec<f64> {
+    if node.is_terminal() {
+        return game.evaluate(node, player, pmi);
+    }
+
+    if node.current_player() == player {
+        node.actions()
+            .map(|action| best_cfvalues_rec(game, &node.play(action), player, pmi, strategy))
             .reduce(|v, w| max_vector(&v, &w))
     } else {
         let current_strategy = &strategy[node.public_history()];
         node.actions()
             .map(|action| {
-                let pmi = mul_vector(&pmi, &current_strategy[action]);
+                let pmi = mul_vector(pmi, &current_strategy[action]);
                 best_cfvalues_rec(game, &node.play(action), player, &pmi, strategy)
             })
             .reduce(|v, w| add_vector(&v, &w))
     }
     .unwrap()
 }
+
+/// 最適応答戦略を再帰的に計算するヘルパー (返り値は `best_cfvalues_rec()` と同じ)
+fn best_response_rec<T: Game>(
+    game: &T,
+    node: &T::Node,
+    player: usize,
+    pmi: &Vec<f64>,
+    strategy: &Strategy,
+    best_response: &mut HashMap<PublicHistory, Vec<Vec<f64>>>,
+) -> Vec<f64> {
+    if node.is_terminal() {
+        return game.evaluate(node, player, pmi);
+    }
+
+    if node.current_player() == player {
+        let cfvalues = node
+            .actions()
+            .map(|action| {
+                best_response_rec(
+                    game,
+                    &node.play(action),
+                    player,
+                    pmi,
+                    strategy,
+                    best_response,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // 手札毎に counterfactual value を最大化するアクションを選ぶ
+        let num_private_hands = game.num_private_hands();
+        let mut probs = vec![vec![0.0; num_private_hands]; node.num_actions()];
+        let mut ret = vec![f64::NEG_INFINITY; num_private_hands];
+        for (action, cfvalue) in cfvalues.iter().enumerate() {
+            for hand in 0..num_private_hands {
+                if cfvalue[hand] > ret[hand] {
+                    ret[hand] = cfvalue[hand];
+                    probs.iter_mut().for_each(|p| p[hand] = 0.0);
+                    probs[action][hand] = 1.0;
+                }
+            }
+        }
+
+        best_response.insert(node.public_history().clone(), probs);
+        ret
+    } else {
+        let current_strategy = &strategy[node.public_history()];
+        node.actions()
+            .map(|action| {
+                let pmi = mul_vector(pmi, &current_strategy[action]);
+                best_response_rec(
+                    game,
+                    &node.play(action),
+                    player,
+                    &pmi,
+                    strategy,
+                    best_response,
+                )
+            })
+            .reduce(|v, w| add_vector(&v, &w))
+            .unwrap()
+    }
+}